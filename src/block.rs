@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+const PARENT_HASH_PREFIX: &str = "System ParentHash";
+const TIMESTAMP_PREFIX: &str = "Timestamp Now";
+const AUTHOR_PREFIX: &str = "XSystem BlockProducer";
+
+/// The metadata of block, which is carried by every pushed message.
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+#[derive(Serialize, Deserialize)]
+pub struct BlockMeta {
+    /// The hash of block, which is known only after the next block has been synchronized,
+    /// (the `System ParentHash` of the next block).
+    pub hash: Option<String>,
+    /// The hash of parent block (`System ParentHash`).
+    pub parent_hash: Option<String>,
+    /// The timestamp of block (`Timestamp Now`).
+    pub timestamp: Option<u64>,
+    /// The author of block (`XSystem BlockProducer`).
+    pub author: Option<String>,
+}
+
+impl BlockMeta {
+    /// Collect the metadata from the parsed storage entries of the block.
+    pub fn from_values(values: &[Value]) -> Self {
+        let mut meta = BlockMeta::default();
+        for value in values {
+            match value["prefix"].as_str() {
+                Some(PARENT_HASH_PREFIX) => {
+                    meta.parent_hash = value["value"].as_str().map(Into::into)
+                }
+                Some(TIMESTAMP_PREFIX) => meta.timestamp = value["value"].as_u64(),
+                Some(AUTHOR_PREFIX) => meta.author = value["value"].as_str().map(Into::into),
+                _ => {}
+            }
+        }
+        meta
+    }
+}
+
+/// The raw key and value of the storage entry.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct RawEntry {
+    pub key: Vec<u8>,
+    pub value: Vec<u8>,
+}
+
+impl RawEntry {
+    pub fn new(key: &[u8], value: &[u8]) -> Self {
+        Self {
+            key: key.to_vec(),
+            value: value.to_vec(),
+        }
+    }
+
+    /// The raw hex of key and value (`{"key":"0x..","value":"0x.."}`).
+    pub fn to_json(&self) -> Value {
+        serde_json::json!({
+            "key": format!("0x{}", hex::encode(&self.key)),
+            "value": format!("0x{}", hex::encode(&self.value)),
+        })
+    }
+}
+
+/// The parsed storage entries of block, with the metadata of block.
+#[derive(PartialEq, Clone, Default, Debug)]
+#[derive(Serialize, Deserialize)]
+pub struct Block {
+    pub meta: BlockMeta,
+    pub values: Vec<Value>,
+    /// The raw entries of the values (by index), which are only kept in memory for the registrants
+    /// that require them, the derived and the passthrough values have no raw entry.
+    #[serde(skip)]
+    pub raw: Vec<Option<RawEntry>>,
+}
+
+impl Block {
+    pub fn new(values: Vec<Value>) -> Self {
+        Self {
+            meta: BlockMeta::from_values(&values),
+            values,
+            raw: vec![],
+        }
+    }
+
+    pub fn with_raw(mut self, raw: Vec<Option<RawEntry>>) -> Self {
+        self.raw = raw;
+        self
+    }
+
+    /// The raw entry of the value at the index.
+    pub fn raw_of(&self, index: usize) -> Option<&RawEntry> {
+        self.raw.get(index).and_then(Option::as_ref)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_block_meta_from_values() {
+        let values = vec![
+            json!({"type":"value", "prefix":"System ParentHash", "key":null, "value":"0x01"}),
+            json!({"type":"value", "prefix":"Timestamp Now", "key":null, "value":1_556_432_000}),
+            json!({"type":"value", "prefix":"XSystem BlockProducer", "key":null, "value":"0x02"}),
+            json!({"type":"map", "prefix":"XAssets AssetList", "key":"ChainX", "value":["PCX"]}),
+        ];
+        assert_eq!(
+            Block::new(values).meta,
+            BlockMeta {
+                hash: None,
+                parent_hash: Some("0x01".into()),
+                timestamp: Some(1_556_432_000),
+                author: Some("0x02".into()),
+            }
+        );
+    }
+}
@@ -2,6 +2,8 @@ use std::path::PathBuf;
 
 use structopt::StructOpt;
 
+use crate::BtcNetwork;
+
 #[derive(Debug, StructOpt)]
 #[structopt(author, about)]
 pub struct CliConfig {
@@ -14,6 +16,43 @@ pub struct CliConfig {
     )]
     pub register_service_port: u16,
 
+    /// Specify the file path that the registrations are persisted to
+    #[structopt(
+        long = "registrations",
+        value_name = "PATH",
+        default_value = "data/registrations.json",
+        parse(from_os_str)
+    )]
+    pub registrations_path: PathBuf,
+
+    /// Specify the count of push retries before the subscription is suspended
+    #[structopt(long = "push-retry-count", value_name = "COUNT", default_value = "10")]
+    pub push_retry_count: u32,
+
+    /// Specify the initial interval of push retries (doubled for every retry), unit: SECOND
+    #[structopt(
+        long = "push-retry-interval",
+        value_name = "SECOND",
+        default_value = "1"
+    )]
+    pub push_retry_interval: u64,
+
+    /// Specify the max interval of push retries and probes, unit: SECOND
+    #[structopt(
+        long = "push-retry-max-interval",
+        value_name = "SECOND",
+        default_value = "60"
+    )]
+    pub push_retry_max_interval: u64,
+
+    /// Specify the duration of suspension after which the subscription is dropped, unit: SECOND (0 means never)
+    #[structopt(
+        long = "dead-letter-after",
+        value_name = "SECOND",
+        default_value = "604800"
+    )]
+    pub dead_letter_after: u64,
+
     /// Specify the parse log file path
     #[structopt(
         long = "parse-log",
@@ -31,6 +70,85 @@ pub struct CliConfig {
     #[structopt(long = "parse-roll-count", value_name = "COUNT", default_value = "5")]
     pub parse_roll_count: u32,
 
+    /// Specify the default bitcoin network until the network id of chain is synced (mainnet or testnet)
+    #[structopt(
+        long = "btc-network",
+        value_name = "NETWORK",
+        default_value = "mainnet"
+    )]
+    pub btc_network: BtcNetwork,
+
+    /// Specify the event registry file path, which replaces the default one (config/events.json)
+    #[structopt(long = "event-registry", value_name = "PATH", parse(from_os_str))]
+    pub event_registry_path: Option<PathBuf>,
+
+    /// Specify the state snapshot file path
+    #[structopt(
+        long = "state-path",
+        value_name = "PATH",
+        default_value = "data/state.json",
+        parse(from_os_str)
+    )]
+    pub state_path: PathBuf,
+
+    /// Specify the interval of saving state snapshot, unit: BLOCK (0 means never)
+    #[structopt(
+        long = "state-snapshot-interval",
+        value_name = "BLOCK",
+        default_value = "100"
+    )]
+    pub state_snapshot_interval: u64,
+
+    /// Specify the storage history file path
+    #[structopt(
+        long = "history-path",
+        value_name = "PATH",
+        default_value = "data/history.jsonl",
+        parse(from_os_str)
+    )]
+    pub history_path: PathBuf,
+
+    /// Disable the storage history
+    #[structopt(long = "no-history")]
+    pub no_history: bool,
+
+    /// Export the snapshot of state to the file (JSON lines) and exit
+    #[structopt(long = "export-snapshot", value_name = "PATH", parse(from_os_str))]
+    pub export_snapshot_path: Option<PathBuf>,
+
+    /// Specify the block height of the exported snapshot (the latest block by default)
+    #[structopt(long = "snapshot-height", value_name = "HEIGHT")]
+    pub snapshot_height: Option<u64>,
+
+    /// Import the snapshot of state from the file before synchronizing
+    #[structopt(long = "import-snapshot", value_name = "PATH", parse(from_os_str))]
+    pub import_snapshot_path: Option<PathBuf>,
+
+    /// Specify the block archive directory
+    #[structopt(
+        long = "archive",
+        value_name = "PATH",
+        default_value = "data/archive",
+        parse(from_os_str)
+    )]
+    pub archive_dir: PathBuf,
+
+    /// Disable the block archive
+    #[structopt(long = "no-archive")]
+    pub no_archive: bool,
+
+    /// Replay the archived blocks from the height to stdout and exit, range: [from,to]
+    #[structopt(long = "replay-from", value_name = "HEIGHT")]
+    pub replay_from: Option<u64>,
+
+    /// Specify the last block height to replay (the latest archived block by default)
+    #[structopt(long = "replay-to", value_name = "HEIGHT")]
+    pub replay_to: Option<u64>,
+
+    /// Print the catalog of runtime storages (JSON) and exit
+    #[structopt(long = "catalog")]
+    pub catalog: bool,
+
     /// Specify the sync log path
     #[cfg(feature = "sync-log")]
     #[structopt(
@@ -0,0 +1,360 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use serde_json::{json, Value};
+
+use super::Derive;
+use crate::StateStore;
+
+/// The ordered withdrawal applications of every chain.
+pub const APPLICATION_LIST: &str = "XAssetsRecords ApplicationList";
+/// The ordered intentions.
+pub const INTENTION_LIST: &str = "XStaking IntentionList";
+/// The ordered intentions (v1).
+pub const INTENTION_LIST_V1: &str = "XStaking IntentionListV1";
+
+macro_rules! build_list_json {
+    ($prefix:expr, $key:expr, $list:expr) => {
+        json!({"type":"linked_list", "prefix":$prefix, "key":$key, "value":$list})
+    };
+}
+
+/// The reconstructor of the lists that are stored as `Node<T>` with prev/next indices,
+/// and whose heads are stored as `MultiNodeIndex` (one list per multi key).
+///
+/// NOTE: the lists are complete only if all nodes have been scanned, i.e. scanning from genesis
+/// or restored from the state store.
+pub struct MultiNodeList {
+    header_prefix: &'static str,
+    node_prefix: &'static str,
+    list_prefix: &'static str,
+    /// multi key (json string) => (multi key, index of the head node)
+    headers: BTreeMap<String, (Value, Value)>,
+    /// node index (json string) => node
+    nodes: HashMap<String, Value>,
+    /// multi key (json string) => the list derived last time
+    lists: BTreeMap<String, Vec<Value>>,
+    changed: bool,
+}
+
+impl MultiNodeList {
+    pub fn new(
+        header_prefix: &'static str,
+        node_prefix: &'static str,
+        list_prefix: &'static str,
+    ) -> Self {
+        Self {
+            header_prefix,
+            node_prefix,
+            list_prefix,
+            headers: BTreeMap::new(),
+            nodes: HashMap::new(),
+            lists: BTreeMap::new(),
+            changed: false,
+        }
+    }
+
+    /// Walk through the list from the head, and collect the data of nodes in order.
+    fn walk(&self, head: &Value) -> Vec<Value> {
+        let mut list = vec![];
+        let mut index = head.clone();
+        // Bound the steps by the number of nodes, in case of the broken links.
+        while !index.is_null() && list.len() <= self.nodes.len() {
+            match self.nodes.get(&index.to_string()) {
+                Some(node) => {
+                    list.push(node["data"].clone());
+                    index = node["next"].clone();
+                }
+                None => {
+                    warn!("[{}] missing node: {}", self.list_prefix, index);
+                    break;
+                }
+            }
+        }
+        list
+    }
+}
+
+impl Derive for MultiNodeList {
+    fn apply(&mut self, value: &Value) {
+        let prefix = value["prefix"].as_str();
+        if prefix == Some(self.header_prefix) {
+            let key = value["key"].to_string();
+            if value["value"].is_null() {
+                self.headers.remove(&key);
+            } else {
+                let index = value["value"]["index"].clone();
+                self.headers.insert(key, (value["key"].clone(), index));
+            }
+            self.changed = true;
+        } else if prefix == Some(self.node_prefix) {
+            let key = value["key"].to_string();
+            if value["value"].is_null() {
+                self.nodes.remove(&key);
+            } else {
+                self.nodes.insert(key, value["value"].clone());
+            }
+            self.changed = true;
+        }
+    }
+
+    fn derive(&mut self) -> Vec<Value> {
+        if !self.changed {
+            return vec![];
+        }
+        self.changed = false;
+
+        let keys = self
+            .headers
+            .keys()
+            .chain(self.lists.keys())
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        let mut derived = vec![];
+        for key in keys {
+            let (multi_key, list) = match self.headers.get(&key) {
+                Some((multi_key, head)) => (multi_key.clone(), self.walk(head)),
+                None => (serde_json::from_str(&key).unwrap_or_default(), vec![]),
+            };
+            if self.lists.get(&key) == Some(&list) {
+                continue;
+            }
+            derived.push(build_list_json!(self.list_prefix, multi_key, list));
+            if self.headers.contains_key(&key) {
+                self.lists.insert(key, list);
+            } else {
+                self.lists.remove(&key);
+            }
+        }
+        derived
+    }
+
+    fn restore(&mut self, store: &StateStore) {
+        self.headers = store
+            .entries(self.header_prefix)
+            .map(|entry| {
+                let index = entry.value["index"].clone();
+                (entry.key.to_string(), (entry.key.clone(), index))
+            })
+            .collect();
+        self.nodes = store
+            .entries(self.node_prefix)
+            .map(|entry| (entry.key.to_string(), entry.value.clone()))
+            .collect();
+        let headers = &self.headers;
+        self.lists = store
+            .entries(self.list_prefix)
+            .filter(|entry| headers.contains_key(&entry.key.to_string()))
+            .filter_map(|entry| Some((entry.key.to_string(), entry.value.as_array()?.clone())))
+            .collect();
+        self.changed = false;
+    }
+}
+
+/// The reconstructor of the list of substrate `linked_map`,
+/// whose value is parsed with `linkage` and whose head key is stored as `head of <prefix>`.
+///
+/// NOTE: the list is complete only if all nodes have been scanned, i.e. scanning from genesis
+/// or restored from the state store.
+pub struct LinkedMapList {
+    head_prefix: &'static str,
+    node_prefix: &'static str,
+    list_prefix: &'static str,
+    /// the key of the head node
+    head: Value,
+    /// key (json string) => (key, value, linkage)
+    nodes: HashMap<String, (Value, Value, Value)>,
+    /// the list derived last time
+    list: Vec<Value>,
+    changed: bool,
+}
+
+impl LinkedMapList {
+    pub fn new(
+        head_prefix: &'static str,
+        node_prefix: &'static str,
+        list_prefix: &'static str,
+    ) -> Self {
+        Self {
+            head_prefix,
+            node_prefix,
+            list_prefix,
+            head: Value::Null,
+            nodes: HashMap::new(),
+            list: vec![],
+            changed: false,
+        }
+    }
+
+    /// Walk through the list from the head, and collect the key and value of nodes in order.
+    fn walk(&self) -> Vec<Value> {
+        let mut list = vec![];
+        let mut key = self.head.clone();
+        // Bound the steps by the number of nodes, in case of the broken links.
+        while !key.is_null() && list.len() <= self.nodes.len() {
+            match self.nodes.get(&key.to_string()) {
+                Some((node_key, node_value, linkage)) => {
+                    list.push(json!({"key": node_key, "value": node_value}));
+                    key = linkage["next"].clone();
+                }
+                None => {
+                    warn!("[{}] missing node: {}", self.list_prefix, key);
+                    break;
+                }
+            }
+        }
+        list
+    }
+}
+
+impl Derive for LinkedMapList {
+    fn apply(&mut self, value: &Value) {
+        let prefix = value["prefix"].as_str();
+        if prefix == Some(self.head_prefix) {
+            self.head = value["value"].clone();
+            self.changed = true;
+        } else if prefix == Some(self.node_prefix) {
+            let key = value["key"].to_string();
+            if value["value"].is_null() {
+                self.nodes.remove(&key);
+            } else {
+                let node = (
+                    value["key"].clone(),
+                    value["value"].clone(),
+                    value["linkage"].clone(),
+                );
+                self.nodes.insert(key, node);
+            }
+            self.changed = true;
+        }
+    }
+
+    fn derive(&mut self) -> Vec<Value> {
+        if !self.changed {
+            return vec![];
+        }
+        self.changed = false;
+
+        let list = self.walk();
+        if list == self.list {
+            return vec![];
+        }
+        self.list = list;
+        vec![build_list_json!(self.list_prefix, Value::Null, &self.list)]
+    }
+
+    fn restore(&mut self, store: &StateStore) {
+        self.head = store
+            .get(self.head_prefix, &Value::Null)
+            .map_or(Value::Null, |entry| entry.value.clone());
+        self.list = store
+            .get(self.list_prefix, &Value::Null)
+            .and_then(|entry| entry.value.as_array().cloned())
+            .unwrap_or_default();
+        // the linkages aren't stored, which are rebuilt from the order of the derived list
+        let keys = self
+            .list
+            .iter()
+            .map(|item| item["key"].clone())
+            .collect::<Vec<_>>();
+        let mut linkages = keys
+            .iter()
+            .enumerate()
+            .map(|(i, key)| {
+                let previous = i.checked_sub(1).map(|i| keys[i].clone());
+                let next = keys.get(i + 1).cloned();
+                (key.to_string(), json!({"previous": previous, "next": next}))
+            })
+            .collect::<HashMap<_, _>>();
+        self.nodes = store
+            .entries(self.node_prefix)
+            .map(|entry| {
+                let key = entry.key.to_string();
+                let linkage = linkages.remove(&key).unwrap_or_default();
+                (key, (entry.key.clone(), entry.value.clone(), linkage))
+            })
+            .collect();
+        self.changed = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_node_list() {
+        let mut deriver = MultiNodeList::new("Header", "Node", "List");
+        let values = vec![
+            json!({"prefix":"Header", "key":"Bitcoin", "value":{"multi_key":"Bitcoin", "index":2}}),
+            json!({"prefix":"Node", "key":1, "value":{"prev":2, "next":null, "data":{"id":1}}}),
+            json!({"prefix":"Node", "key":2, "value":{"prev":null, "next":1, "data":{"id":2}}}),
+        ];
+        values.iter().for_each(|value| deriver.apply(value));
+        assert_eq!(
+            deriver.derive(),
+            vec![build_list_json!(
+                "List",
+                "Bitcoin",
+                json!([{"id":2}, {"id":1}])
+            )]
+        );
+        // nothing changed
+        assert!(deriver.derive().is_empty());
+
+        // remove the head node
+        let values = vec![
+            json!({"prefix":"Header", "key":"Bitcoin", "value":{"multi_key":"Bitcoin", "index":1}}),
+            json!({"prefix":"Node", "key":2, "value":null}),
+            json!({"prefix":"Node", "key":1, "value":{"prev":null, "next":null, "data":{"id":1}}}),
+        ];
+        values.iter().for_each(|value| deriver.apply(value));
+        assert_eq!(
+            deriver.derive(),
+            vec![build_list_json!("List", "Bitcoin", json!([{"id":1}]))]
+        );
+
+        // remove the list
+        let values = vec![
+            json!({"prefix":"Header", "key":"Bitcoin", "value":null}),
+            json!({"prefix":"Node", "key":1, "value":null}),
+        ];
+        values.iter().for_each(|value| deriver.apply(value));
+        assert_eq!(
+            deriver.derive(),
+            vec![build_list_json!("List", "Bitcoin", json!([]))]
+        );
+        assert!(deriver.derive().is_empty());
+    }
+
+    #[test]
+    fn test_linked_map_list() {
+        let mut deriver = LinkedMapList::new("head of Map", "Map", "List");
+        let values = vec![
+            json!({"prefix":"head of Map", "key":null, "value":"0x01"}),
+            json!({"prefix":"Map", "key":"0x01", "value":100, "linkage":{"previous":null, "next":"0x02"}}),
+            json!({"prefix":"Map", "key":"0x02", "value":200, "linkage":{"previous":"0x01", "next":null}}),
+        ];
+        values.iter().for_each(|value| deriver.apply(value));
+        assert_eq!(
+            deriver.derive(),
+            vec![build_list_json!(
+                "List",
+                Value::Null,
+                json!([{"key":"0x01", "value":100}, {"key":"0x02", "value":200}])
+            )]
+        );
+
+        // update the value of node
+        let value = json!({"prefix":"Map", "key":"0x02", "value":300, "linkage":{"previous":"0x01", "next":null}});
+        deriver.apply(&value);
+        assert_eq!(
+            deriver.derive(),
+            vec![build_list_json!(
+                "List",
+                Value::Null,
+                json!([{"key":"0x01", "value":100}, {"key":"0x02", "value":300}])
+            )]
+        );
+        assert!(deriver.derive().is_empty());
+    }
+}
@@ -0,0 +1,167 @@
+mod linked_list;
+mod sdot;
+mod trustee;
+
+use serde_json::Value;
+
+use crate::StateStore;
+
+use self::linked_list::{LinkedMapList, MultiNodeList};
+use self::sdot::SdotClaims;
+use self::trustee::TrusteeMultiSig;
+
+pub use self::linked_list::{APPLICATION_LIST, INTENTION_LIST, INTENTION_LIST_V1};
+pub use self::sdot::SDOT_CLAIMS;
+pub use self::trustee::TRUSTEE_MULTISIG;
+
+/// The prefixes of all derived entries.
+pub const DERIVED_PREFIXES: &[&str] = &[
+    APPLICATION_LIST,
+    INTENTION_LIST,
+    INTENTION_LIST_V1,
+    TRUSTEE_MULTISIG,
+    SDOT_CLAIMS,
+];
+
+/// A stateful deriver, which tracks the parsed storage entries across blocks
+/// and derives new entries from them.
+pub trait Derive: Send {
+    /// Apply the parsed storage entry to the state of deriver.
+    fn apply(&mut self, value: &Value);
+    /// Derive the entries that have changed since the last derivation.
+    fn derive(&mut self) -> Vec<Value>;
+    /// Restore the state of deriver from the latest values of the state store (including
+    /// the entries derived last time), so that the sync can resume from the stored block.
+    fn restore(&mut self, store: &StateStore);
+}
+
+/// The collection of all derivers.
+pub struct Derivations {
+    derivers: Vec<Box<dyn Derive>>,
+}
+
+impl Default for Derivations {
+    fn default() -> Self {
+        Self {
+            derivers: vec![
+                Box::new(MultiNodeList::new(
+                    "XAssetsRecords ApplicationMHeader",
+                    "XAssetsRecords ApplicationMap",
+                    APPLICATION_LIST,
+                )),
+                Box::new(LinkedMapList::new(
+                    "head of XStaking Intentions",
+                    "XStaking Intentions",
+                    INTENTION_LIST,
+                )),
+                Box::new(LinkedMapList::new(
+                    "head of XStaking IntentionsV1",
+                    "XStaking IntentionsV1",
+                    INTENTION_LIST_V1,
+                )),
+                Box::new(TrusteeMultiSig::new()),
+                Box::new(SdotClaims::new()),
+            ],
+        }
+    }
+}
+
+impl Derivations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Restore the state of all derivers from the state store.
+    pub fn restore(&mut self, store: &StateStore) {
+        for deriver in self.derivers.iter_mut() {
+            deriver.restore(store);
+        }
+    }
+
+    /// Apply all parsed storage entries of a block, and return the derived entries.
+    pub fn derive_block(&mut self, values: &[Value]) -> Vec<Value> {
+        let mut derived = vec![];
+        for deriver in self.derivers.iter_mut() {
+            values
+                .iter()
+                .filter(|value| is_parsed(value))
+                .for_each(|value| deriver.apply(value));
+            derived.extend(deriver.derive());
+        }
+        derived
+    }
+}
+
+/// Check whether the storage entry has been parsed successfully.
+fn is_parsed(value: &Value) -> bool {
+    !matches!(value["type"].as_str(), Some("unknown") | Some("error"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn node(key: &str, value: u64, previous: Option<&str>, next: Option<&str>) -> Value {
+        json!({
+            "type": "map",
+            "prefix": "XStaking Intentions",
+            "key": key,
+            "value": value,
+            "linkage": {"previous": previous, "next": next},
+        })
+    }
+
+    /// Derive the block and apply it to the store, as the sync does.
+    fn sync_block(
+        derivations: &mut Derivations,
+        store: &mut StateStore,
+        height: u64,
+        mut values: Vec<Value>,
+    ) -> Vec<Value> {
+        let derived = derivations.derive_block(&values);
+        values.extend(derived.clone());
+        store.apply_block(height, &values).unwrap();
+        derived
+    }
+
+    #[test]
+    fn test_derivations_restore() {
+        let mut store = StateStore::new();
+        let mut derivations = Derivations::new();
+        let values = vec![
+            json!({"type":"value", "prefix":"head of XStaking Intentions", "key":null, "value":"0x01"}),
+            node("0x01", 100, None, Some("0x02")),
+            node("0x02", 200, Some("0x01"), None),
+            json!({"type":"map", "prefix":"XAssetsRecords ApplicationMHeader", "key":"Bitcoin", "value":{"index":1}}),
+            json!({"type":"map", "prefix":"XAssetsRecords ApplicationMap", "key":1, "value":{"prev":null, "next":2, "data":{"id":1}}}),
+            json!({"type":"map", "prefix":"XAssetsRecords ApplicationMap", "key":2, "value":{"prev":1, "next":null, "data":{"id":2}}}),
+        ];
+        assert_eq!(sync_block(&mut derivations, &mut store, 1, values).len(), 2);
+
+        // restart, only the nodes touched after the restart are synced
+        let mut derivations = Derivations::new();
+        derivations.restore(&store);
+        let values = vec![
+            node("0x02", 300, Some("0x01"), None),
+            json!({"type":"map", "prefix":"XAssetsRecords ApplicationMap", "key":2, "value":{"prev":1, "next":null, "data":{"id":2, "balance":10}}}),
+        ];
+        let derived = sync_block(&mut derivations, &mut store, 2, values);
+        assert_eq!(derived.len(), 2);
+        assert_eq!(
+            derived[0]["value"],
+            json!([{"id":1}, {"id":2, "balance":10}])
+        );
+        assert_eq!(
+            derived[1]["value"],
+            json!([{"key":"0x01", "value":100}, {"key":"0x02", "value":300}])
+        );
+
+        // nothing changed since the restore
+        let mut derivations = Derivations::new();
+        derivations.restore(&store);
+        assert!(derivations
+            .derive_block(&[node("0x01", 100, None, Some("0x02"))])
+            .is_empty());
+    }
+}
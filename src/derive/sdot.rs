@@ -0,0 +1,247 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::{json, Value};
+
+use super::Derive;
+use crate::StateStore;
+
+/// The SDOT claims of every ethereum address, with the bound ChainX account.
+pub const SDOT_CLAIMS: &str = "XBridgeOfSDOT ClaimList";
+
+const CLAIMS_PREFIX: &str = "XBridgeOfSDOT Claims";
+const TOTAL_PREFIX: &str = "XBridgeOfSDOT Total";
+const CROSS_CHAIN_OF_PREFIX: &str = "XBridgeFeatures EthereumCrossChainOf";
+
+/// The summary of SDOT claims, i.e. the claimable and claimed amount of every ethereum address,
+/// the total unclaimed (`XBridgeOfSDOT Total`) and claimed amount,
+/// and the ChainX account (and channel) that every ethereum address is bound to.
+///
+/// `XBridgeOfSDOT Claims` holds the amount that is still claimable, which is removed on claim,
+/// so the amount before the removal is recorded as the claimed amount.
+///
+/// NOTE: the summary is complete only if the blocks are scanned from genesis or restored from
+/// the state store, where the claimed amounts are only kept in the summary derived last time.
+#[derive(Default)]
+pub struct SdotClaims {
+    /// ethereum address => claimable amount
+    claimable: BTreeMap<String, u64>,
+    /// ethereum address => claimed amount
+    claimed: BTreeMap<String, u64>,
+    /// ethereum address => (account, channel)
+    bindings: BTreeMap<String, Value>,
+    total: Value,
+    /// the summary derived last time
+    summary: Value,
+    changed: bool,
+}
+
+impl SdotClaims {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn apply_claim(&mut self, key: &Value, value: Option<u64>) {
+        let address = match key.as_str() {
+            Some(address) => address.to_string(),
+            None => return,
+        };
+        match value {
+            Some(amount) => {
+                self.claimable.insert(address, amount);
+            }
+            None => {
+                if let Some(amount) = self.claimable.remove(&address) {
+                    *self.claimed.entry(address).or_default() += amount;
+                }
+            }
+        }
+    }
+
+    fn summarize(&self) -> Value {
+        let addresses = self
+            .claimable
+            .keys()
+            .chain(self.claimed.keys())
+            .collect::<BTreeSet<_>>();
+        let claims = addresses
+            .into_iter()
+            .map(|address| {
+                let binding = self.bindings.get(address);
+                json!({
+                    "address": address,
+                    "claimable": self.claimable.get(address).copied().unwrap_or_default(),
+                    "claimed": self.claimed.get(address).copied().unwrap_or_default(),
+                    "account": binding.map(|binding| &binding[0]),
+                    "channel": binding.map(|binding| &binding[1]),
+                })
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "total": self.total,
+            "unclaimed": self.claimable.values().sum::<u64>(),
+            "claimed": self.claimed.values().sum::<u64>(),
+            "claims": claims,
+        })
+    }
+}
+
+fn update<V>(map: &mut BTreeMap<String, V>, key: &Value, value: Option<V>) {
+    let key = match key.as_str() {
+        Some(key) => key.to_string(),
+        None => return,
+    };
+    match value {
+        Some(value) => map.insert(key, value),
+        None => map.remove(&key),
+    };
+}
+
+impl Derive for SdotClaims {
+    fn apply(&mut self, value: &Value) {
+        match value["prefix"].as_str() {
+            Some(CLAIMS_PREFIX) => self.apply_claim(&value["key"], value["value"].as_u64()),
+            Some(CROSS_CHAIN_OF_PREFIX) => {
+                let binding = Some(value["value"].clone()).filter(|binding| !binding.is_null());
+                update(&mut self.bindings, &value["key"], binding)
+            }
+            Some(TOTAL_PREFIX) => self.total = value["value"].clone(),
+            _ => return,
+        }
+        self.changed = true;
+    }
+
+    fn derive(&mut self) -> Vec<Value> {
+        if !self.changed {
+            return vec![];
+        }
+        self.changed = false;
+
+        let summary = self.summarize();
+        if summary == self.summary {
+            return vec![];
+        }
+        self.summary = summary.clone();
+        vec![json!({
+            "type": "sdot_claims",
+            "prefix": SDOT_CLAIMS,
+            "key": null,
+            "value": summary,
+        })]
+    }
+
+    fn restore(&mut self, store: &StateStore) {
+        let latest = |prefix| {
+            store
+                .get(prefix, &Value::Null)
+                .map_or(Value::Null, |entry| entry.value.clone())
+        };
+        self.claimable = store
+            .entries(CLAIMS_PREFIX)
+            .filter_map(|entry| Some((entry.key.as_str()?.to_string(), entry.value.as_u64()?)))
+            .collect();
+        self.bindings = store
+            .entries(CROSS_CHAIN_OF_PREFIX)
+            .filter_map(|entry| Some((entry.key.as_str()?.to_string(), entry.value.clone())))
+            .collect();
+        self.total = latest(TOTAL_PREFIX);
+        self.summary = latest(SDOT_CLAIMS);
+        self.claimed = self.summary["claims"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|claim| {
+                let amount = claim["claimed"].as_u64().filter(|amount| *amount > 0)?;
+                Some((claim["address"].as_str()?.to_string(), amount))
+            })
+            .collect();
+        self.changed = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sdot_claims() {
+        let address = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let mut deriver = SdotClaims::new();
+        deriver.apply(&json!({"type":"value", "prefix":TOTAL_PREFIX, "key":null, "value":3000}));
+        deriver.apply(&json!({"type":"map", "prefix":CLAIMS_PREFIX, "key":address, "value":1000}));
+        deriver.apply(&json!({
+            "type":"map",
+            "prefix":CROSS_CHAIN_OF_PREFIX,
+            "key":address,
+            "value":["0x01", null],
+        }));
+        assert_eq!(
+            deriver.derive(),
+            vec![json!({
+                "type":"sdot_claims",
+                "prefix":SDOT_CLAIMS,
+                "key":null,
+                "value":{
+                    "total":3000,
+                    "unclaimed":1000,
+                    "claimed":0,
+                    "claims":[{"address":address, "claimable":1000, "claimed":0, "account":"0x01", "channel":null}],
+                },
+            })]
+        );
+
+        // nothing changed
+        deriver.apply(&json!({"type":"value", "prefix":TOTAL_PREFIX, "key":null, "value":3000}));
+        assert!(deriver.derive().is_empty());
+
+        // the claim is removed when it's claimed
+        deriver.apply(&json!({"type":"map", "prefix":CLAIMS_PREFIX, "key":address, "value":null}));
+        deriver.apply(&json!({"type":"value", "prefix":TOTAL_PREFIX, "key":null, "value":2000}));
+        let derived = deriver.derive();
+        assert_eq!(derived[0]["value"]["total"], 2000);
+        assert_eq!(derived[0]["value"]["unclaimed"], 0);
+        assert_eq!(derived[0]["value"]["claimed"], 1000);
+        assert_eq!(
+            derived[0]["value"]["claims"],
+            json!([{"address":address, "claimable":0, "claimed":1000, "account":"0x01", "channel":null}])
+        );
+    }
+
+    #[test]
+    fn test_sdot_claims_restore() {
+        /// Derive the block and apply it to the store, return the stored summary.
+        fn sync_block(
+            deriver: &mut SdotClaims,
+            store: &mut StateStore,
+            height: u64,
+            values: Vec<Value>,
+        ) -> Value {
+            values.iter().for_each(|value| deriver.apply(value));
+            let derived = deriver.derive();
+            let block = values.into_iter().chain(derived).collect::<Vec<_>>();
+            store.apply_block(height, &block).unwrap();
+            store.get(SDOT_CLAIMS, &Value::Null).unwrap().value.clone()
+        }
+
+        let address = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+        let claim = |value: Value| json!({"type":"map", "prefix":CLAIMS_PREFIX, "key":address, "value":value});
+        let mut store = StateStore::new();
+        let mut deriver = SdotClaims::new();
+        sync_block(&mut deriver, &mut store, 1, vec![claim(json!(1000))]);
+        let summary = sync_block(&mut deriver, &mut store, 2, vec![claim(Value::Null)]);
+        assert_eq!(summary["claimed"], 1000);
+
+        // restart
+        let mut deriver = SdotClaims::new();
+        deriver.restore(&store);
+        assert_eq!(deriver.summarize(), summary);
+        let summary = sync_block(&mut deriver, &mut store, 3, vec![claim(json!(500))]);
+        assert_eq!(summary["claims"][0]["claimed"], 1000);
+        assert_eq!(summary["claims"][0]["claimable"], 500);
+
+        // the block #3 is applied again on the fallback, after the state is rolled back
+        store.rollback(3).unwrap();
+        deriver.restore(&store);
+        let summary = sync_block(&mut deriver, &mut store, 3, vec![claim(Value::Null)]);
+        assert_eq!(summary["claimed"], 1000);
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde_json::{json, Value};
+
+use super::Derive;
+use crate::StateStore;
+
+/// The trustees of the multisig addresses of every bitcoin trustee session.
+pub const TRUSTEE_MULTISIG: &str = "XBridgeFeatures BitcoinTrusteeMultiSig";
+
+const INTENTION_PROPS_PREFIX: &str = "XBridgeFeatures BitcoinTrusteeIntentionPropertiesOf";
+const SESSION_INFO_PREFIX: &str = "XBridgeFeatures BitcoinTrusteeSessionInfoOf";
+
+/// The matcher of the public keys of the multisig redeem scripts of trustee sessions
+/// and the trustee accounts (by the hot/cold entities of trustee intention properties).
+///
+/// NOTE: the accounts are complete only if the trustee properties have been scanned,
+/// i.e. scanning from genesis or restored from the state store.
+#[derive(Default)]
+pub struct TrusteeMultiSig {
+    /// public key (hex string) => (account, "hot" or "cold")
+    entities: HashMap<String, (Value, &'static str)>,
+    /// account (json string) => public keys of hot and cold entity
+    props: HashMap<String, (Value, Value)>,
+    /// session number (json string) => (session number, session info)
+    sessions: BTreeMap<String, (Value, Value)>,
+    /// session number (json string) => the multisig derived last time
+    derived: BTreeMap<String, Value>,
+    changed: bool,
+}
+
+impl TrusteeMultiSig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn update_props(&mut self, account: &Value, props: &Value) {
+        if let Some((hot, cold)) = self.props.remove(&account.to_string()) {
+            for pubkey in [hot, cold].iter().filter_map(Value::as_str) {
+                self.entities.remove(pubkey);
+            }
+        }
+        if props.is_null() {
+            return;
+        }
+        let (hot, cold) = (props["hot_entity"].clone(), props["cold_entity"].clone());
+        if let Some(pubkey) = hot.as_str() {
+            self.entities
+                .insert(pubkey.to_string(), (account.clone(), "hot"));
+        }
+        if let Some(pubkey) = cold.as_str() {
+            self.entities
+                .insert(pubkey.to_string(), (account.clone(), "cold"));
+        }
+        self.props.insert(account.to_string(), (hot, cold));
+    }
+
+    /// Match the ordered public keys of the multisig address with the trustee accounts.
+    fn match_address(&self, addr_info: &Value) -> Value {
+        let multisig = &addr_info["multisig"];
+        let trustees = multisig["pubkeys"]
+            .as_array()
+            .map(|pubkeys| {
+                pubkeys
+                    .iter()
+                    .map(|pubkey| {
+                        let (account, entity) = pubkey
+                            .as_str()
+                            .and_then(|pubkey| self.entities.get(pubkey))
+                            .map(|(account, entity)| (account.clone(), Value::from(*entity)))
+                            .unwrap_or_default();
+                        json!({"pubkey": pubkey, "account": account, "entity": entity})
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        json!({
+            "addr": addr_info["addr"],
+            "required": multisig["required"],
+            "total": multisig["total"],
+            "address_verified": addr_info["address_verified"],
+            "trustees": trustees,
+        })
+    }
+}
+
+impl Derive for TrusteeMultiSig {
+    fn apply(&mut self, value: &Value) {
+        let prefix = value["prefix"].as_str();
+        if prefix == Some(INTENTION_PROPS_PREFIX) {
+            self.update_props(&value["key"], &value["value"]);
+            self.changed = true;
+        } else if prefix == Some(SESSION_INFO_PREFIX) {
+            let key = value["key"].to_string();
+            if value["value"].is_null() {
+                self.sessions.remove(&key);
+            } else {
+                self.sessions
+                    .insert(key, (value["key"].clone(), value["value"].clone()));
+            }
+            self.changed = true;
+        }
+    }
+
+    fn derive(&mut self) -> Vec<Value> {
+        if !self.changed {
+            return vec![];
+        }
+        self.changed = false;
+
+        let mut derived = vec![];
+        for (key, (session, info)) in &self.sessions {
+            let multisig = json!({
+                "hot_address": self.match_address(&info["hot_address"]),
+                "cold_address": self.match_address(&info["cold_address"]),
+            });
+            if self.derived.get(key) == Some(&multisig) {
+                continue;
+            }
+            derived.push(json!({
+                "type": "trustee_multisig",
+                "prefix": TRUSTEE_MULTISIG,
+                "key": session,
+                "value": multisig,
+            }));
+            self.derived.insert(key.clone(), multisig);
+        }
+        let sessions = &self.sessions;
+        for (key, _) in self
+            .derived
+            .iter()
+            .filter(|(key, _)| !sessions.contains_key(*key))
+        {
+            let session = serde_json::from_str::<Value>(key).unwrap_or_default();
+            derived.push(json!({
+                "type": "trustee_multisig",
+                "prefix": TRUSTEE_MULTISIG,
+                "key": session,
+                "value": Value::Null,
+            }));
+        }
+        self.derived.retain(|key, _| sessions.contains_key(key));
+        derived
+    }
+
+    fn restore(&mut self, store: &StateStore) {
+        self.entities.clear();
+        self.props.clear();
+        for entry in store.entries(INTENTION_PROPS_PREFIX) {
+            self.update_props(&entry.key, &entry.value);
+        }
+        self.sessions = store
+            .entries(SESSION_INFO_PREFIX)
+            .map(|entry| {
+                (
+                    entry.key.to_string(),
+                    (entry.key.clone(), entry.value.clone()),
+                )
+            })
+            .collect();
+        self.derived = store
+            .entries(TRUSTEE_MULTISIG)
+            .map(|entry| (entry.key.to_string(), entry.value.clone()))
+            .collect();
+        self.changed = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr_info(addr: &str, pubkeys: &[&str]) -> Value {
+        json!({
+            "addr": addr,
+            "redeem_script": "0x",
+            "multisig": {"required": 2, "total": pubkeys.len(), "pubkeys": pubkeys},
+            "address_verified": true,
+        })
+    }
+
+    #[test]
+    fn test_trustee_multisig() {
+        let mut deriver = TrusteeMultiSig::new();
+        deriver.apply(&json!({
+            "type": "map",
+            "prefix": SESSION_INFO_PREFIX,
+            "key": 0,
+            "value": {
+                "trustee_list": ["0x01", "0x02"],
+                "hot_address": addr_info("3Hot", &["0xa1", "0xa2"]),
+                "cold_address": addr_info("3Cold", &["0xb2", "0xb1"]),
+            },
+        }));
+        deriver.apply(&json!({
+            "type": "map",
+            "prefix": INTENTION_PROPS_PREFIX,
+            "key": "0x01",
+            "value": {"about": "", "hot_entity": "0xa1", "cold_entity": "0xb1"},
+        }));
+        let derived = deriver.derive();
+        assert_eq!(derived.len(), 1);
+        assert_eq!(derived[0]["prefix"], TRUSTEE_MULTISIG);
+        assert_eq!(derived[0]["key"], 0);
+        assert_eq!(derived[0]["value"]["hot_address"]["required"], 2);
+        assert_eq!(
+            derived[0]["value"]["hot_address"]["trustees"],
+            json!([
+                {"pubkey": "0xa1", "account": "0x01", "entity": "hot"},
+                {"pubkey": "0xa2", "account": null, "entity": null},
+            ])
+        );
+        assert_eq!(
+            derived[0]["value"]["cold_address"]["trustees"][1],
+            json!({"pubkey": "0xb1", "account": "0x01", "entity": "cold"})
+        );
+
+        // nothing changed
+        assert!(deriver.derive().is_empty());
+
+        deriver.apply(&json!({
+            "type": "map",
+            "prefix": INTENTION_PROPS_PREFIX,
+            "key": "0x02",
+            "value": {"about": "", "hot_entity": "0xa2", "cold_entity": "0xb2"},
+        }));
+        let derived = deriver.derive();
+        assert_eq!(derived.len(), 1);
+        assert_eq!(
+            derived[0]["value"]["hot_address"]["trustees"][1]["account"],
+            "0x02"
+        );
+    }
+
+    #[test]
+    fn test_trustee_multisig_restore() {
+        let mut deriver = TrusteeMultiSig::new();
+        let values = vec![
+            json!({
+                "type": "map",
+                "prefix": SESSION_INFO_PREFIX,
+                "key": 0,
+                "value": {
+                    "trustee_list": ["0x01"],
+                    "hot_address": addr_info("3Hot", &["0xa1", "0xa2"]),
+                    "cold_address": addr_info("3Cold", &["0xb1"]),
+                },
+            }),
+            json!({
+                "type": "map",
+                "prefix": INTENTION_PROPS_PREFIX,
+                "key": "0x01",
+                "value": {"about": "", "hot_entity": "0xa1", "cold_entity": "0xb1"},
+            }),
+        ];
+        values.iter().for_each(|value| deriver.apply(value));
+        let mut store = StateStore::new();
+        let derived = deriver.derive();
+        store
+            .apply_block(1, &values.into_iter().chain(derived).collect::<Vec<_>>())
+            .unwrap();
+
+        // restart, the keys are still matched with the trustees seen before the restart
+        let mut deriver = TrusteeMultiSig::new();
+        deriver.restore(&store);
+        assert!(deriver.derive().is_empty());
+        deriver.apply(&json!({
+            "type": "map",
+            "prefix": INTENTION_PROPS_PREFIX,
+            "key": "0x02",
+            "value": {"about": "", "hot_entity": "0xa2", "cold_entity": "0xb2"},
+        }));
+        let derived = deriver.derive();
+        assert_eq!(derived.len(), 1);
+        assert_eq!(
+            derived[0]["value"]["hot_address"]["trustees"],
+            json!([
+                {"pubkey": "0xa1", "account": "0x01", "entity": "hot"},
+                {"pubkey": "0xa2", "account": "0x02", "entity": "hot"},
+            ])
+        );
+    }
+}
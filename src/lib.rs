@@ -1,25 +1,26 @@
 #[macro_use]
 extern crate log;
 
+mod block;
 pub mod cli;
+mod derive;
 mod error;
 pub mod logger;
 mod parse;
+mod queue;
 mod register;
+mod store;
 #[cfg(feature = "sync-log")]
 mod sync;
 mod types;
 
-use std::collections::BTreeMap;
-use std::sync::Arc;
-
-use parking_lot::RwLock;
-
+pub use self::block::{Block, BlockMeta, RawEntry};
 pub use self::cli::CliConfig;
+pub use self::derive::Derivations;
 pub use self::error::{Error, Result};
-pub use self::parse::RuntimeStorage;
-pub use self::register::RegisterService;
+pub use self::parse::{catalog, load_event_registry, split_events, BtcNetwork, RuntimeStorage};
+pub use self::queue::{BlockQueue, Queue};
+pub use self::register::{PushConfig, RegisterService};
+pub use self::store::{BlockArchive, SharedBlockArchive, SharedStateStore, StateStore};
 #[cfg(feature = "sync-log")]
 pub use self::sync::*;
-
-pub type BlockQueue = Arc<RwLock<BTreeMap<u64, Vec<serde_json::Value>>>>;
@@ -2,21 +2,77 @@
 extern crate log;
 
 use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
 use std::thread::JoinHandle;
+use std::time::Duration;
+
+use parking_lot::RwLock;
 
 use chainx_sync_parse::*;
 
 fn main() -> Result<()> {
     let config = cli::init();
+
+    if config.catalog {
+        println!("{}", serde_json::to_string_pretty(&catalog())?);
+        return Ok(());
+    }
+
+    // the replay output is written to stdout, without the console log
+    if let Some(from) = config.replay_from {
+        return replay_archive(&config, from);
+    }
+
     logger::init(&config)?;
 
+    if let Some(path) = &config.event_registry_path {
+        load_event_registry(path)?;
+    }
+
     let block_queue: BlockQueue = BlockQueue::default();
+    let mut store = StateStore::open(&config.state_path, config.state_snapshot_interval)?;
+    if !config.no_history {
+        store = store.with_history(&config.history_path)?;
+    }
+    if let Some(path) = &config.export_snapshot_path {
+        return export_snapshot(&store, config.snapshot_height, path);
+    }
+    if let Some(path) = &config.import_snapshot_path {
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        store.import_snapshot(file)?;
+    }
+    let store: SharedStateStore = Arc::new(RwLock::new(store));
+    let archive: Option<SharedBlockArchive> = if config.no_archive {
+        None
+    } else {
+        Some(Arc::new(RwLock::new(BlockArchive::open(
+            &config.archive_dir,
+        )?)))
+    };
 
-    let register_server = RegisterService::new(block_queue.clone())
-        .run(&format!("0.0.0.0:{}", config.register_service_port))?;
+    let push_config = PushConfig {
+        retry_count: config.push_retry_count,
+        initial_interval: Duration::from_secs(config.push_retry_interval),
+        max_interval: Duration::from_secs(config.push_retry_max_interval),
+        dead_letter_after: match config.dead_letter_after {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        },
+        ..Default::default()
+    };
+    let mut register_service = RegisterService::new(block_queue.clone())
+        .persist_registrations(config.registrations_path.clone())
+        .state_store(store.clone())
+        .push_config(push_config);
+    if let Some(archive) = &archive {
+        register_service = register_service.block_archive(archive.clone());
+    }
+    let register_server =
+        register_service.run(&format!("0.0.0.0:{}", config.register_service_port))?;
 
     #[cfg(feature = "sync-log")]
-    let sync_service = sync_log(&config, &block_queue)?;
+    let sync_service = sync_log(&config, &block_queue, &store, &archive)?;
 
     #[cfg(feature = "sync-log")]
     sync_service
@@ -29,42 +85,100 @@ fn main() -> Result<()> {
 }
 
 #[cfg(feature = "sync-log")]
-fn sync_log(config: &CliConfig, queue: &BlockQueue) -> Result<JoinHandle<()>> {
+fn sync_log(
+    config: &CliConfig,
+    queue: &BlockQueue,
+    store: &SharedStateStore,
+    archive: &Option<SharedBlockArchive>,
+) -> Result<JoinHandle<()>> {
+    // resume from the block after the stored state, instead of syncing the stored blocks again
+    let start_height = match store.read().height() {
+        Some(height) if height >= config.start_height => {
+            info!("Resume from the stored block #{}", height);
+            height + 1
+        }
+        _ => config.start_height,
+    };
     assert!(
-        config.start_height < config.stop_height,
+        start_height < config.stop_height,
         "Invalid block height range"
     );
     info!(
         "Scanned block height range, [start: {}, stop: {})",
-        config.start_height, config.stop_height
+        start_height, config.stop_height
     );
 
     let tail = Tail::new();
-    let sync_service = tail.run(config)?;
+    let sync_service = tail.run(config, start_height)?;
 
     let mut stat = HashMap::new();
-    let mut next_block_height: u64 = config.start_height;
+    let mut derivations = Derivations::new();
+    // the derivers resume with the stored state instead of the blocks before the start height
+    derivations.restore(&store.read());
+    // the bitcoin network of chain, the option is only the default until the network id is known
+    let mut btc_network =
+        RuntimeStorage::stored_btc_network(&store.read()).unwrap_or(config.btc_network);
+    let mut next_block_height: u64 = start_height;
 
     while let Ok((height, key, value)) = tail.recv_data() {
         debug_sync_block_info(height, &key, &value);
 
         // handling sync block fallback
         if height < next_block_height {
-            insert_block_into_queue(queue, next_block_height, &stat);
+            insert_block_into_queue(
+                queue,
+                store,
+                archive,
+                next_block_height,
+                &stat,
+                &mut derivations,
+            );
             next_block_height = height;
             stat.clear();
         }
 
         // collect all data of the block with the same height
         if height == next_block_height {
-            match RuntimeStorage::parse(&key, value) {
+            let raw_value = value.clone();
+            let (prefix, value) = match RuntimeStorage::parse_with_network(&key, value, btc_network)
+            {
                 Ok((prefix, value)) => {
-                    let mut prefix = prefix.as_bytes().to_vec();
-                    prefix.extend_from_slice(&key);
-                    stat.insert(prefix, value);
+                    if let Some(network) = RuntimeStorage::btc_network(&value) {
+                        if network != btc_network {
+                            info!("Block #{}: switch to the bitcoin {}", height, network);
+                            btc_network = network;
+                        }
+                    }
+                    (prefix.to_string(), value)
                 }
-                Err(_) => continue,
+                Err(err) => match RuntimeStorage::passthrough(&key, &raw_value, &err) {
+                    Some(entry) => {
+                        debug!(
+                            "Block #{}: unable to parse storage [{}], forward it as is",
+                            height,
+                            hex::encode(&key)
+                        );
+                        entry
+                    }
+                    None => {
+                        debug!(
+                            "Block #{}: unable to parse well-known storage [{}], skip it",
+                            height,
+                            String::from_utf8_lossy(&key)
+                        );
+                        continue;
+                    }
+                },
+            };
+            // the decoded events can be subscribed by `Module.Event` separately
+            for event in split_events(&value) {
+                let prefix = format!("{}{}", event["prefix"], event["key"]);
+                stat.insert(prefix.into_bytes(), (event, None));
             }
+            let raw = RawEntry::new(&key, &raw_value);
+            let mut prefix = prefix.into_bytes();
+            prefix.extend_from_slice(&key);
+            stat.insert(prefix, (value, Some(raw)));
         } else {
             // when height > nex_block_height
             // Insert a complete block into queue.
@@ -72,7 +186,14 @@ fn sync_log(config: &CliConfig, queue: &BlockQueue) -> Result<JoinHandle<()>> {
             // it means that the block0 (height = 0) has been synchronized and parsed.
             assert!(height >= 1);
             let insert_height = height - 1;
-            insert_block_into_queue(queue, insert_height, &stat);
+            insert_block_into_queue(
+                queue,
+                store,
+                archive,
+                insert_height,
+                &stat,
+                &mut derivations,
+            );
             next_block_height = height;
             stat.clear();
         }
@@ -81,23 +202,83 @@ fn sync_log(config: &CliConfig, queue: &BlockQueue) -> Result<JoinHandle<()>> {
     Ok(sync_service)
 }
 
-fn insert_block_into_queue(queue: &BlockQueue, h: u64, stat: &HashMap<Vec<u8>, serde_json::Value>) {
-    let values = stat.values().cloned().collect::<Vec<_>>();
-    if queue.write().insert(h, values.clone()).is_none() {
-        info!("Insert new block #{} into block queue successfully", h);
-        info!(
-            "Block #{}: {:?}",
-            h,
-            serde_json::Value::Array(values).to_string()
+fn insert_block_into_queue(
+    queue: &BlockQueue,
+    store: &SharedStateStore,
+    archive: &Option<SharedBlockArchive>,
+    h: u64,
+    stat: &HashMap<Vec<u8>, (serde_json::Value, Option<RawEntry>)>,
+    derivations: &mut Derivations,
+) {
+    let (mut values, raw): (Vec<_>, Vec<_>) = stat.values().cloned().unzip();
+    // the block is applied again on the sync fallback,
+    // so the derivers restart with the state before the block
+    if store.read().height().map_or(false, |latest| h <= latest) {
+        let mut store = store.write();
+        if let Err(err) = store.rollback(h) {
+            error!("Block #{}: unable to roll back state store: {}", h, err);
+        }
+        derivations.restore(&store);
+    }
+    let derived = derivations.derive_block(&values);
+    values.extend(derived);
+    let block = Block::new(values.clone()).with_raw(raw);
+    if let Err(err) = store.write().apply_block(h, &values) {
+        error!(
+            "Block #{}: unable to apply block into state store: {}",
+            h, err
         );
+    }
+    if let Some(archive) = archive {
+        if let Err(err) = archive.write().append(h, &block) {
+            error!("Block #{}: unable to append block into archive: {}", h, err);
+        }
+    }
+    let is_new = {
+        let mut queue = queue.write();
+        // The hash of the parent block is only known from this block.
+        if let Some(parent) = h.checked_sub(1).and_then(|parent| queue.get_mut(&parent)) {
+            parent.meta.hash = block.meta.parent_hash.clone();
+        }
+        queue.insert(h, block).is_none()
+    };
+    // wake up the push threads
+    queue.notify();
+    if is_new {
+        info!("Insert new block #{} into block queue successfully", h);
     } else {
         info!("Insert updated block #{} into block queue successfully", h);
-        info!(
-            "Block #{}: {:?}",
-            h,
-            serde_json::Value::Array(values).to_string()
-        );
     }
+    debug!(
+        "Block #{}: {:?}",
+        h,
+        serde_json::Value::Array(values).to_string()
+    );
+}
+
+fn replay_archive(config: &CliConfig, from: u64) -> Result<()> {
+    let archive = BlockArchive::open(&config.archive_dir)?;
+    let to = match (config.replay_to, archive.heights()) {
+        (Some(to), _) => to,
+        (None, Some((_, max))) => max,
+        (None, None) => return Ok(()),
+    };
+    if from > to {
+        return Err("Invalid block height range".into());
+    }
+    let stdout = std::io::stdout();
+    archive.replay(from, to, stdout.lock())?;
+    Ok(())
+}
+
+fn export_snapshot(store: &StateStore, height: Option<u64>, path: &Path) -> Result<()> {
+    let height = match height.or_else(|| store.height()) {
+        Some(height) => height,
+        None => return Err("Empty state store".into()),
+    };
+    let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    store.export_snapshot(height, file)?;
+    Ok(())
 }
 
 fn debug_sync_block_info(height: u64, key: &[u8], value: &[u8]) {
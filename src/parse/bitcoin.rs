@@ -0,0 +1,774 @@
+//! Enriched serialization of the bitcoin structures of `light-bitcoin`.
+//!
+//! The hashes (block hash, txid, ...) are rendered in the display (reversed) byte order without `0x`,
+//! which is consistent with the bitcoin block explorers.
+
+use std::cell::Cell;
+use std::fmt;
+use std::str::FromStr;
+
+use byteorder::{ByteOrder, LittleEndian};
+use parity_codec::{Decode, Encode};
+use ripemd160::Ripemd160;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use super::primitives::from_ss58check;
+use crate::types::btc;
+use crate::types::schema::{self, nullable, object, uint, Schema};
+
+/// The network of bitcoin that the addresses are rendered for.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum BtcNetwork {
+    Mainnet,
+    Testnet,
+}
+
+impl Default for BtcNetwork {
+    fn default() -> Self {
+        BtcNetwork::Mainnet
+    }
+}
+
+impl BtcNetwork {
+    /// The network id of `XBridgeOfBTC NetworkId`.
+    pub fn id(self) -> u32 {
+        match self {
+            BtcNetwork::Mainnet => 0,
+            BtcNetwork::Testnet => 1,
+        }
+    }
+
+    /// The network of `XBridgeOfBTC NetworkId`, `None` for the unknown id.
+    pub fn from_id(id: u32) -> Option<Self> {
+        match id {
+            0 => Some(BtcNetwork::Mainnet),
+            1 => Some(BtcNetwork::Testnet),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for BtcNetwork {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "mainnet" => Ok(BtcNetwork::Mainnet),
+            "testnet" => Ok(BtcNetwork::Testnet),
+            _ => Err(format!("Invalid bitcoin network: {:?}", s)),
+        }
+    }
+}
+
+impl fmt::Display for BtcNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BtcNetwork::Mainnet => write!(f, "mainnet"),
+            BtcNetwork::Testnet => write!(f, "testnet"),
+        }
+    }
+}
+
+thread_local! {
+    /// The network of the current decoding, which is only set by `with_network`,
+    /// since the serialization of bitcoin structures can't take any argument.
+    static NETWORK: Cell<BtcNetwork> = Cell::new(BtcNetwork::default());
+}
+
+/// Decode (and serialize) the bitcoin structures for the network within the closure.
+pub fn with_network<T>(network: BtcNetwork, f: impl FnOnce() -> T) -> T {
+    let prev = NETWORK.with(|current| current.replace(network));
+    let result = f();
+    NETWORK.with(|current| current.set(prev));
+    result
+}
+
+fn current_network() -> BtcNetwork {
+    NETWORK.with(Cell::get)
+}
+
+fn is_mainnet() -> bool {
+    current_network() == BtcNetwork::Mainnet
+}
+
+/// Bitcoin block header, serialized with block hash.
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+#[derive(Encode, Decode)]
+pub struct BlockHeader(pub btc::BlockHeader);
+
+impl Serialize for BlockHeader {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let raw = to_bitcoin_bytes(&self.0);
+        // the malformed header is serialized as the raw hex
+        match header_to_json(&raw) {
+            Some(header) => header.serialize(serializer),
+            None => serializer.serialize_str(&to_hex(&raw)),
+        }
+    }
+}
+
+impl Schema for BlockHeader {
+    fn schema() -> Value {
+        json!({"anyOf": [
+            object(vec![
+                ("hash", hash_schema()),
+                ("version", uint()),
+                ("prev_hash", hash_schema()),
+                ("merkle_root", hash_schema()),
+                ("time", uint()),
+                ("bits", uint()),
+                ("nonce", uint()),
+            ]),
+            schema::hex(None),
+        ]})
+    }
+}
+
+impl<'de> Deserialize<'de> for BlockHeader {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        btc::BlockHeader::deserialize(deserializer).map(BlockHeader)
+    }
+}
+
+/// Bitcoin transaction, serialized with txid/wtxid, decoded inputs and outputs.
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+#[derive(Encode, Decode)]
+pub struct Transaction(pub btc::Transaction);
+
+impl Serialize for Transaction {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let raw = to_bitcoin_bytes(&self.0);
+        // the malformed transaction is serialized as the raw hex
+        match tx_to_json(&raw) {
+            Some(tx) => tx.serialize(serializer),
+            None => serializer.serialize_str(&to_hex(&raw)),
+        }
+    }
+}
+
+impl Schema for Transaction {
+    fn schema() -> Value {
+        let input = object(vec![
+            ("prev_txid", hash_schema()),
+            ("prev_index", uint()),
+            ("script_sig", schema::hex(None)),
+            ("sequence", uint()),
+            (
+                "witness",
+                json!({"type": "array", "items": schema::hex(None)}),
+            ),
+        ]);
+        let mut output = object(vec![
+            ("value", uint()),
+            ("script_pubkey", schema::hex(None)),
+            ("type", json!({"type": "string"})),
+            ("address", nullable(json!({"type": "string"}))),
+        ]);
+        // only the `op_return` output has the memo
+        output["properties"]["memo"] = nullable(object(vec![
+            ("text", json!({"type": "string"})),
+            ("account", nullable(schema::hex(Some(32)))),
+            ("channel", nullable(json!({"type": "string"}))),
+        ]));
+        json!({"anyOf": [
+            object(vec![
+                ("txid", hash_schema()),
+                ("wtxid", hash_schema()),
+                ("version", uint()),
+                ("inputs", json!({"type": "array", "items": input})),
+                ("outputs", json!({"type": "array", "items": output})),
+                ("lock_time", uint()),
+            ]),
+            schema::hex(None),
+        ]})
+    }
+}
+
+impl<'de> Deserialize<'de> for Transaction {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        btc::Transaction::deserialize(deserializer).map(Transaction)
+    }
+}
+
+/// Bitcoin address, serialized as base58 string.
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+#[derive(Encode, Decode)]
+pub struct Address(pub btc::Address);
+
+impl Serialize for Address {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(&self.0)
+    }
+}
+
+impl Schema for Address {
+    fn schema() -> Value {
+        json!({"type": "string", "description": "base58 address"})
+    }
+}
+
+impl<'de> Deserialize<'de> for Address {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        btc::Address::deserialize(deserializer).map(Address)
+    }
+}
+
+/// Bitcoin public key, serialized as hex string.
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+#[derive(Encode, Decode)]
+pub struct Public(pub btc::Public);
+
+impl Serialize for Public {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&to_hex(&self.0))
+    }
+}
+
+impl Schema for Public {
+    fn schema() -> Value {
+        schema::hex(None)
+    }
+}
+
+impl<'de> Deserialize<'de> for Public {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        btc::Public::deserialize(deserializer).map(Public)
+    }
+}
+
+/// Get the bitcoin serialization of the `light-bitcoin` structure,
+/// which is encoded as the `Vec<u8>` of the bitcoin serialization.
+fn to_bitcoin_bytes<T: Encode>(t: &T) -> Vec<u8> {
+    let encoded = t.encode();
+    let mut input = encoded.as_slice();
+    match Vec::<u8>::decode(&mut input) {
+        Some(raw) if input.is_empty() => raw,
+        _ => encoded,
+    }
+}
+
+pub fn dhash256(data: &[u8]) -> [u8; 32] {
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&Sha256::digest(&Sha256::digest(data)));
+    hash
+}
+
+pub fn hash160(data: &[u8]) -> [u8; 20] {
+    let mut hash = [0u8; 20];
+    hash.copy_from_slice(&Ripemd160::digest(&Sha256::digest(data)));
+    hash
+}
+
+/// The hash in the display byte order, without `0x`.
+fn hash_schema() -> Value {
+    json!({"type": "string", "pattern": "^[0-9a-f]{64}$"})
+}
+
+/// Render the hash in the display (reversed) byte order.
+fn to_display_hex(hash: &[u8]) -> String {
+    hex::encode(hash.iter().rev().cloned().collect::<Vec<_>>())
+}
+
+fn to_hex(data: &[u8]) -> String {
+    format!("0x{}", hex::encode(data))
+}
+
+/// The reader of bitcoin serialization.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn is_end(&self) -> bool {
+        self.pos == self.data.len()
+    }
+
+    fn peek(&self, offset: usize) -> Option<u8> {
+        self.data.get(self.pos + offset).cloned()
+    }
+
+    fn read(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(len)?;
+        let data = self.data.get(self.pos..end)?;
+        self.pos = end;
+        Some(data)
+    }
+
+    fn read_u8(&mut self) -> Option<u8> {
+        self.read(1).map(|data| data[0])
+    }
+
+    fn read_u16(&mut self) -> Option<u16> {
+        self.read(2).map(LittleEndian::read_u16)
+    }
+
+    fn read_u32(&mut self) -> Option<u32> {
+        self.read(4).map(LittleEndian::read_u32)
+    }
+
+    fn read_u64(&mut self) -> Option<u64> {
+        self.read(8).map(LittleEndian::read_u64)
+    }
+
+    fn read_compact(&mut self) -> Option<u64> {
+        match self.read_u8()? {
+            0xfd => self.read_u16().map(u64::from),
+            0xfe => self.read_u32().map(u64::from),
+            0xff => self.read_u64(),
+            n => Some(u64::from(n)),
+        }
+    }
+
+    fn read_var_bytes(&mut self) -> Option<&'a [u8]> {
+        let len = self.read_compact()?;
+        self.read(len as usize)
+    }
+}
+
+fn header_to_json(raw: &[u8]) -> Option<Value> {
+    if raw.len() != 80 {
+        return None;
+    }
+    let mut reader = Reader::new(raw);
+    let version = reader.read_u32()?;
+    let prev_hash = reader.read(32)?;
+    let merkle_root = reader.read(32)?;
+    let time = reader.read_u32()?;
+    let bits = reader.read_u32()?;
+    let nonce = reader.read_u32()?;
+    Some(json!({
+        "hash": to_display_hex(&dhash256(raw)),
+        "version": version,
+        "prev_hash": to_display_hex(prev_hash),
+        "merkle_root": to_display_hex(merkle_root),
+        "time": time,
+        "bits": bits,
+        "nonce": nonce,
+    }))
+}
+
+fn tx_to_json(raw: &[u8]) -> Option<Value> {
+    let mut reader = Reader::new(raw);
+    let version = reader.read_u32()?;
+    let has_witness = reader.peek(0) == Some(0) && reader.peek(1) == Some(1);
+    if has_witness {
+        reader.read(2)?;
+    }
+
+    let body_start = reader.pos;
+    let input_count = reader.read_compact()?;
+    let mut inputs = vec![];
+    for _ in 0..input_count {
+        let prev_txid = reader.read(32)?;
+        let prev_index = reader.read_u32()?;
+        let script_sig = reader.read_var_bytes()?;
+        let sequence = reader.read_u32()?;
+        inputs.push(json!({
+            "prev_txid": to_display_hex(prev_txid),
+            "prev_index": prev_index,
+            "script_sig": to_hex(script_sig),
+            "sequence": sequence,
+            "witness": [],
+        }));
+    }
+    let output_count = reader.read_compact()?;
+    let mut outputs = vec![];
+    for _ in 0..output_count {
+        let value = reader.read_u64()?;
+        let script = reader.read_var_bytes()?;
+        outputs.push(output_to_json(value, script));
+    }
+    let body_end = reader.pos;
+
+    if has_witness {
+        for input in inputs.iter_mut() {
+            let item_count = reader.read_compact()?;
+            let mut items = vec![];
+            for _ in 0..item_count {
+                items.push(Value::String(to_hex(reader.read_var_bytes()?)));
+            }
+            input["witness"] = Value::Array(items);
+        }
+    }
+    let lock_time = reader.read_u32()?;
+    if !reader.is_end() {
+        return None;
+    }
+
+    // txid is the hash of the serialization without witness.
+    let mut base = raw[..4].to_vec();
+    base.extend_from_slice(&raw[body_start..body_end]);
+    base.extend_from_slice(&raw[raw.len() - 4..]);
+    Some(json!({
+        "txid": to_display_hex(&dhash256(&base)),
+        "wtxid": to_display_hex(&dhash256(raw)),
+        "version": version,
+        "inputs": inputs,
+        "outputs": outputs,
+        "lock_time": lock_time,
+    }))
+}
+
+/// The value of output is in satoshis.
+fn output_to_json(value: u64, script: &[u8]) -> Value {
+    let (script_type, address) = script_to_address(script);
+    let mut output = json!({
+        "value": value,
+        "script_pubkey": to_hex(script),
+        "type": script_type,
+        "address": address,
+    });
+    if script_type == "op_return" {
+        output["memo"] = op_return_payload(script)
+            .map(|payload| memo_to_json(&payload))
+            .unwrap_or_default();
+    }
+    output
+}
+
+/// Get the type of output script, and render the script as address if it's standard.
+pub fn script_to_address(script: &[u8]) -> (&'static str, Option<String>) {
+    let (p2pkh, p2sh) = if is_mainnet() {
+        (0x00, 0x05)
+    } else {
+        (0x6f, 0xc4)
+    };
+    match script {
+        [0x76, 0xa9, 0x14, hash @ .., 0x88, 0xac] if hash.len() == 20 => {
+            ("p2pkh", Some(base58_address(p2pkh, hash)))
+        }
+        [0xa9, 0x14, hash @ .., 0x87] if hash.len() == 20 => {
+            ("p2sh", Some(base58_address(p2sh, hash)))
+        }
+        [0x00, 0x14, program @ ..] if program.len() == 20 => {
+            ("p2wpkh", Some(segwit_address(0, program)))
+        }
+        [0x00, 0x20, program @ ..] if program.len() == 32 => {
+            ("p2wsh", Some(segwit_address(0, program)))
+        }
+        [0x6a, ..] => ("op_return", None),
+        [len, pubkey @ .., 0xac] if (*len == 33 || *len == 65) && pubkey.len() == *len as usize => {
+            ("p2pk", Some(base58_address(p2pkh, &hash160(pubkey))))
+        }
+        _ => ("nonstandard", None),
+    }
+}
+
+/// Decode the multisig redeem script (`OP_m <pubkey>... OP_n OP_CHECKMULTISIG`)
+/// into the required signatures, total keys and the ordered public keys.
+pub fn multisig_to_json(script: &[u8]) -> Option<Value> {
+    let (&op_m, rest) = script.split_first()?;
+    let (&op_checkmultisig, rest) = rest.split_last()?;
+    let (&op_n, mut pubkeys_script) = rest.split_last()?;
+    if op_checkmultisig != 0xae || !is_op_num(op_m) || !is_op_num(op_n) {
+        return None;
+    }
+    let (required, total) = (op_m - 0x50, op_n - 0x50);
+
+    let mut pubkeys = vec![];
+    while let Some((&len, rest)) = pubkeys_script.split_first() {
+        if (len != 33 && len != 65) || rest.len() < len as usize {
+            return None;
+        }
+        let (pubkey, rest) = rest.split_at(len as usize);
+        pubkeys.push(to_hex(pubkey));
+        pubkeys_script = rest;
+    }
+    if required > total || pubkeys.len() != total as usize {
+        return None;
+    }
+    Some(json!({
+        "required": required,
+        "total": total,
+        "pubkeys": pubkeys,
+    }))
+}
+
+/// The schema of `multisig_to_json`, `null` if the script isn't a multisig script.
+pub fn multisig_schema() -> Value {
+    nullable(object(vec![
+        ("required", uint()),
+        ("total", uint()),
+        (
+            "pubkeys",
+            json!({"type": "array", "items": schema::hex(None)}),
+        ),
+    ]))
+}
+
+/// OP_1 ~ OP_16
+fn is_op_num(op: u8) -> bool {
+    (0x51..=0x60).contains(&op)
+}
+
+/// Check whether the base58 address is the P2SH address (of any network) of the script.
+pub fn is_p2sh_address_of(address: &str, script: &[u8]) -> bool {
+    let payload = match bs58::decode(address).into_vec() {
+        Ok(payload) if payload.len() == 25 => payload,
+        _ => return false,
+    };
+    let (body, checksum) = payload.split_at(21);
+    (body[0] == 0x05 || body[0] == 0xc4)
+        && dhash256(body)[..4] == *checksum
+        && body[1..] == hash160(script)
+}
+
+fn base58_address(version: u8, hash: &[u8]) -> String {
+    let mut payload = vec![version];
+    payload.extend_from_slice(hash);
+    let checksum = dhash256(&payload);
+    payload.extend_from_slice(&checksum[..4]);
+    bs58::encode(payload).into_string()
+}
+
+/// Render the segwit address in bech32 (BIP-173).
+fn segwit_address(version: u8, program: &[u8]) -> String {
+    const CHARSET: &[u8] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+    let hrp = if is_mainnet() { "bc" } else { "tb" };
+
+    let mut data = vec![version];
+    data.extend(convert_bits(program, 8, 5));
+    let mut values = hrp.bytes().map(|b| b >> 5).collect::<Vec<_>>();
+    values.push(0);
+    values.extend(hrp.bytes().map(|b| b & 31));
+    values.extend(&data);
+    values.extend(&[0u8; 6]);
+    let polymod = bech32_polymod(&values) ^ 1;
+    let checksum = (0..6).map(|i| ((polymod >> (5 * (5 - i))) & 31) as u8);
+
+    let mut address = format!("{}1", hrp);
+    address.extend(
+        data.into_iter()
+            .chain(checksum)
+            .map(|v| CHARSET[v as usize] as char),
+    );
+    address
+}
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GENERATOR: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+    let mut chk: u32 = 1;
+    for value in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(*value);
+        for (i, generator) in GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= generator;
+            }
+        }
+    }
+    chk
+}
+
+/// Regroup the bits of data, with padding.
+fn convert_bits(data: &[u8], from: u32, to: u32) -> Vec<u8> {
+    let max_value = (1 << to) - 1;
+    let max_acc = (1 << (from + to - 1)) - 1;
+    let mut acc: u32 = 0;
+    let mut bits = 0;
+    let mut result = vec![];
+    for value in data {
+        acc = ((acc << from) | u32::from(*value)) & max_acc;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            result.push(((acc >> bits) & max_value) as u8);
+        }
+    }
+    if bits > 0 {
+        result.push(((acc << (to - bits)) & max_value) as u8);
+    }
+    result
+}
+
+/// Concatenate all data pushed by the OP_RETURN script.
+fn op_return_payload(script: &[u8]) -> Option<Vec<u8>> {
+    let mut reader = Reader::new(script.get(1..)?);
+    let mut payload = vec![];
+    while !reader.is_end() {
+        let len = match reader.read_u8()? {
+            op @ 0x01..=0x4b => usize::from(op),
+            0x4c => usize::from(reader.read_u8()?),
+            0x4d => usize::from(reader.read_u16()?),
+            0x4e => reader.read_u32()? as usize,
+            _ => return None,
+        };
+        payload.extend_from_slice(reader.read(len)?);
+    }
+    Some(payload)
+}
+
+/// Decode the ChainX deposit memo (`<ss58 account>[@<channel>]`).
+fn memo_to_json(payload: &[u8]) -> Value {
+    let text = String::from_utf8_lossy(payload);
+    let (account, channel) = match text.find('@') {
+        Some(index) => (&text[..index], Some(&text[index + 1..])),
+        None => (&text[..], None),
+    };
+    json!({
+        "text": text,
+        "account": from_ss58check(account),
+        "channel": channel,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_to_json() {
+        let raw = hex::decode("00000020c62c8e792a2ede1bfd654e15b5ae47b92657a9af18a3451bf500000000000000472fe9a5af55712bea58af70cd5111daa8feb8c78393d0e2cf2de369ce2a21c95870945c21fb001adedd69f3").unwrap();
+        assert_eq!(
+            header_to_json(&raw).unwrap(),
+            json!({
+                "hash": "000000000000004a38f07e3e4cf4638c3c367576c907cdcbf2bd7bd2e6347326",
+                "version": 536_870_912,
+                "prev_hash": "00000000000000f51b45a318afa95726b947aeb5154e65fd1bde2e2a798e2cc6",
+                "merkle_root": "c9212ace69e32dcfe2d09383c7b8fea8da1151cd70af58ea2b7155afa5e92f47",
+                "time": 1_553_231_960,
+                "bits": 436_271_905,
+                "nonce": 4_083_801_566u32,
+            })
+        );
+    }
+
+    #[test]
+    fn test_tx_to_json() {
+        // genesis coinbase
+        let raw = hex::decode("01000000010000000000000000000000000000000000000000000000000000000000000000ffffffff4d04ffff001d0104455468652054696d65732030332f4a616e2f32303039204368616e63656c6c6f72206f6e206272696e6b206f66207365636f6e64206261696c6f757420666f722062616e6b73ffffffff0100f2052a01000000434104678afdb0fe5548271967f1a67130b7105cd6a828e03909a67962e0ea1f61deb649f6bc3f4cef38c4f35504e51ec112de5c384df7ba0b8d578a4c702b6bf11d5fac00000000").unwrap();
+        let tx = tx_to_json(&raw).unwrap();
+        let txid = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+        assert_eq!(tx["txid"], txid);
+        assert_eq!(tx["wtxid"], txid);
+        assert_eq!(tx["outputs"][0]["value"], 5_000_000_000u64);
+        assert_eq!(tx["outputs"][0]["type"], "p2pk");
+        assert_eq!(
+            tx["outputs"][0]["address"],
+            "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa"
+        );
+
+        // segwit, with OP_RETURN memo
+        let raw = hex::decode("0200000000010111111111111111111111111111111111111111111111111111111111111111110100000000ffffffff02a086010000000000160014751e76e8199196d454941c45d1b3a323f1433bd60000000000000000386a363550664c696a39486371394157316338616943755333656f5378436a74394a415277437476613755646a784a7266797140416c6963650203aabbcc02ddee00000000").unwrap();
+        let tx = tx_to_json(&raw).unwrap();
+        assert_eq!(
+            tx["txid"],
+            "82a5998019e2188ed4fcc3b2ea08475b047f538da2de9c7837aca71db92964ec"
+        );
+        assert_eq!(
+            tx["wtxid"],
+            "de884a338766f08bedafd734bd2eb34592cf98ba5950d5adb5a4abadb81d2b2d"
+        );
+        assert_eq!(tx["inputs"][0]["prev_index"], 1);
+        assert_eq!(tx["inputs"][0]["witness"], json!(["0xaabbcc", "0xddee"]));
+        assert_eq!(tx["outputs"][0]["value"], 100_000);
+        assert_eq!(tx["outputs"][0]["type"], "p2wpkh");
+        assert_eq!(
+            tx["outputs"][0]["address"],
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+        assert_eq!(tx["outputs"][1]["type"], "op_return");
+        assert_eq!(
+            tx["outputs"][1]["memo"],
+            json!({
+                "text": "5PfLij9Hcq9AW1c8aiCuS3eoSxCjt9JARwCtva7UdjxJrfyq@Alice",
+                "account": format!("0x{}", hex::encode([1u8; 32])),
+                "channel": "Alice",
+            })
+        );
+    }
+
+    #[test]
+    fn test_multisig_to_json() {
+        let pubkeys = [[2u8; 33], [3u8; 33], [4u8; 33]];
+        let mut script = vec![0x52];
+        for pubkey in pubkeys.iter() {
+            script.push(33);
+            script.extend_from_slice(pubkey);
+        }
+        script.extend_from_slice(&[0x53, 0xae]);
+        assert_eq!(
+            multisig_to_json(&script).unwrap(),
+            json!({
+                "required": 2,
+                "total": 3,
+                "pubkeys": pubkeys.iter().map(|pubkey| to_hex(pubkey)).collect::<Vec<_>>(),
+            })
+        );
+
+        // total keys mismatch
+        let len = script.len();
+        script[len - 2] = 0x54;
+        assert_eq!(multisig_to_json(&script), None);
+        assert_eq!(multisig_to_json(&[0x51, 0x51, 0xae]), None);
+    }
+
+    #[test]
+    fn test_is_p2sh_address_of() {
+        let script = [0x51];
+        let hash = hash160(&script);
+        let mainnet = base58_address(0x05, &hash);
+        let testnet = base58_address(0xc4, &hash);
+        assert!(is_p2sh_address_of(&mainnet, &script));
+        assert!(is_p2sh_address_of(&testnet, &script));
+        assert!(!is_p2sh_address_of(&base58_address(0x00, &hash), &script));
+        assert!(!is_p2sh_address_of(&testnet, &[0x52]));
+        assert!(!is_p2sh_address_of("invalid", &script));
+    }
+
+    #[test]
+    fn test_script_to_address() {
+        let script = hex::decode("a9144e944b03e84fdc97f2fb68cb62b73d000ef5be7187").unwrap();
+        let (script_type, _) = script_to_address(&script);
+        assert_eq!(script_type, "p2sh");
+        let script = hex::decode("0014751e76e8199196d454941c45d1b3a323f1433bd6").unwrap();
+        assert_eq!(
+            script_to_address(&script).1.unwrap(),
+            "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4"
+        );
+        assert_eq!(
+            with_network(BtcNetwork::Testnet, || script_to_address(&script).1).unwrap(),
+            "tb1qw508d6qejxtdg4y5r3zarvary0c5xw7kxpjzsx"
+        );
+        // the network is restored after the closure
+        assert!(is_mainnet());
+        assert_eq!(script_to_address(&[0x51]), ("nonstandard", None));
+    }
+}
@@ -0,0 +1,408 @@
+//! Decoding of `System Events` with the data-driven event registry.
+//!
+//! The registry describes the module/event names and the argument types of every event
+//! by their indices, the default one (`config/events.json`) follows the ChainX mainnet runtime,
+//! and it can be replaced by `--event-registry` when the runtime is upgraded.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use parity_codec::{Compact, Decode};
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use super::primitives::*;
+use crate::Result;
+
+const DEFAULT_EVENT_REGISTRY: &str = include_str!("../../config/events.json");
+
+lazy_static::lazy_static! {
+    static ref EVENT_REGISTRY: RwLock<EventRegistry> = RwLock::new(
+        EventRegistry::from_json(DEFAULT_EVENT_REGISTRY).expect("The default event registry must be valid")
+    );
+    /// The unsupported argument types that have been warned about.
+    static ref UNSUPPORTED_ARG_TYPES: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
+}
+
+/// Replace the event registry with the one in the file.
+pub fn load_event_registry(path: &Path) -> Result<()> {
+    let registry = EventRegistry::from_json(&std::fs::read_to_string(path)?)?;
+    info!("Load event registry from {:?}", path);
+    *EVENT_REGISTRY.write() = registry;
+    Ok(())
+}
+
+/// The prefixes (`Module.Event`) of all events in the event registry.
+pub fn event_prefixes() -> Vec<String> {
+    EVENT_REGISTRY
+        .read()
+        .modules
+        .values()
+        .flat_map(|module| {
+            module
+                .events
+                .iter()
+                .map(move |event| format!("{}.{}", module.module, event.name))
+        })
+        .collect()
+}
+
+/// The prefixes (`Module.Event`) of the events that carry `AccountId` in their arguments,
+/// with the JSON pointers (to the event entry) of the `AccountId`s, e.g. `/value/args/0`.
+pub fn account_event_prefixes() -> Vec<(String, Vec<String>)> {
+    EVENT_REGISTRY
+        .read()
+        .modules
+        .values()
+        .flat_map(|module| {
+            module.events.iter().filter_map(move |event| {
+                let pointers = event
+                    .args
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(index, ty)| account_pointers(ty, format!("/value/args/{}", index)))
+                    .collect::<Vec<_>>();
+                if pointers.is_empty() {
+                    return None;
+                }
+                Some((format!("{}.{}", module.module, event.name), pointers))
+            })
+        })
+        .collect()
+}
+
+/// The JSON pointers of the `AccountId`s in the argument of the type, whose `*` segment matches
+/// every element of `Vec`, e.g. `Vec<(AccountId, u32)>` => `<base>/*/0`.
+fn account_pointers(ty: &str, base: String) -> Vec<String> {
+    let ty = ty.trim();
+    if ty == "AccountId" {
+        return vec![base];
+    }
+    if let Some(inner) = strip_generic(ty, "Vec") {
+        return account_pointers(inner, format!("{}/*", base));
+    }
+    if let Some(inner) = strip_generic(ty, "Option") {
+        return account_pointers(inner, base);
+    }
+    if ty.starts_with('(') && ty.ends_with(')') {
+        return split_top_level(&ty[1..ty.len() - 1])
+            .into_iter()
+            .enumerate()
+            .flat_map(|(index, ty)| account_pointers(ty, format!("{}/{}", base, index)))
+            .collect();
+    }
+    vec![]
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ModuleMeta {
+    module: String,
+    index: u8,
+    events: Vec<EventMeta>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct EventMeta {
+    name: String,
+    args: Vec<String>,
+}
+
+/// The module/event names and argument types, indexed by module index.
+#[derive(Clone, Debug, Default)]
+pub struct EventRegistry {
+    modules: BTreeMap<u8, ModuleMeta>,
+}
+
+impl EventRegistry {
+    pub fn from_json(s: &str) -> Result<Self> {
+        let modules: Vec<ModuleMeta> = serde_json::from_str(s)?;
+        Ok(Self {
+            modules: modules
+                .into_iter()
+                .map(|module| (module.index, module))
+                .collect(),
+        })
+    }
+
+    fn get(&self, module: u8, event: u8) -> Option<(&str, &EventMeta)> {
+        let meta = self.modules.get(&module)?;
+        let event = meta.events.get(usize::from(event))?;
+        Some((&meta.module, event))
+    }
+}
+
+/// The phase of the block execution that the event is emitted in.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(Decode)]
+#[derive(Serialize)]
+enum Phase {
+    ApplyExtrinsic(u32),
+    Finalization,
+}
+
+/// Decode the `Vec<EventRecord>` of `System Events` into `[{phase, module, event, args}]`.
+///
+/// The decoding stops at the first event that is unknown or fails to be decoded,
+/// since the length of its arguments is unknown, and the record is reported with
+/// the `error` and the remaining bytes.
+pub fn events_to_json(value: &[u8]) -> Option<Value> {
+    let mut input = value;
+    let len = <Compact<u32>>::decode(&mut input)?.0;
+    let registry = EVENT_REGISTRY.read();
+    // every event takes at least one byte, the length may be corrupted
+    let mut events = Vec::with_capacity(len.min(input.len() as u32) as usize);
+    for _ in 0..len {
+        let remaining = input;
+        match decode_event(&registry, &mut input) {
+            Ok(event) => events.push(event),
+            Err(err) => {
+                events.push(json!({
+                    "error": err,
+                    "remaining": format!("0x{}", hex::encode(remaining)),
+                }));
+                break;
+            }
+        }
+    }
+    Some(Value::Array(events))
+}
+
+fn decode_event(registry: &EventRegistry, input: &mut &[u8]) -> std::result::Result<Value, String> {
+    let phase = Phase::decode(input).ok_or("Decode phase failed")?;
+    let (module, event) = <(u8, u8)>::decode(input).ok_or("Decode event index failed")?;
+    let (module_name, meta) = registry
+        .get(module, event)
+        .ok_or_else(|| format!("Unknown event, module: {}, event: {}", module, event))?;
+    let args = meta
+        .args
+        .iter()
+        .map(|ty| {
+            decode_arg(ty, input).ok_or_else(|| {
+                format!(
+                    "Decode argument `{}` of {}.{} failed",
+                    ty, module_name, meta.name
+                )
+            })
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(json!({
+        "phase": phase,
+        "module": module_name,
+        "event": meta.name,
+        "args": args,
+    }))
+}
+
+/// Split the decoded `System Events` into the entries of every event,
+/// whose prefix is `Module.Event` and key is the index of event in the block.
+pub fn split_events(events: &Value) -> Vec<Value> {
+    events["value"]
+        .as_array()
+        .map(|events| {
+            events
+                .iter()
+                .enumerate()
+                .filter_map(|(index, event)| {
+                    let module = event["module"].as_str()?;
+                    let name = event["event"].as_str()?;
+                    Some(json!({
+                        "type": "event",
+                        "prefix": format!("{}.{}", module, name),
+                        "key": index,
+                        "value": event,
+                    }))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn decode_as<T: Decode + Serialize>(input: &mut &[u8]) -> Option<Value> {
+    T::decode(input).and_then(|value| serde_json::to_value(value).ok())
+}
+
+/// Decode the argument by the name of type, e.g. `AccountId`, `Vec<(AccountId, u32)>`.
+fn decode_arg(ty: &str, input: &mut &[u8]) -> Option<Value> {
+    let ty = ty.trim();
+    if let Some(inner) = strip_generic(ty, "Vec") {
+        let len = <Compact<u32>>::decode(input)?.0;
+        return (0..len)
+            .map(|_| decode_arg(inner, input))
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array);
+    }
+    if let Some(inner) = strip_generic(ty, "Option") {
+        return match u8::decode(input)? {
+            0 => Some(Value::Null),
+            1 => decode_arg(inner, input),
+            _ => None,
+        };
+    }
+    if ty.starts_with('(') && ty.ends_with(')') {
+        return split_top_level(&ty[1..ty.len() - 1])
+            .into_iter()
+            .map(|ty| decode_arg(ty, input))
+            .collect::<Option<Vec<_>>>()
+            .map(Value::Array);
+    }
+    match ty {
+        "bool" => decode_as::<bool>(input),
+        "u8" => decode_as::<u8>(input),
+        "u16" => decode_as::<u16>(input),
+        "u32" => decode_as::<u32>(input),
+        "u64" => decode_as::<u64>(input),
+        "H256" => decode_as::<H256>(input),
+        "H512" => decode_as::<H512>(input),
+        "Bytes" => decode_as::<crate::types::Bytes>(input),
+        "AccountId" | "SessionKey" => decode_as::<AccountId>(input),
+        "AccountIndex" => decode_as::<AccountIndex>(input),
+        "Balance" | "Price" => decode_as::<Balance>(input),
+        "BlockNumber" | "Timestamp" => decode_as::<BlockNumber>(input),
+        "OrderIndex" | "TradeHistoryIndex" => decode_as::<OrderIndex>(input),
+        "TradingPairIndex" => decode_as::<TradingPairIndex>(input),
+        "XString" | "Token" | "Name" | "URL" | "Memo" | "AddrStr" => decode_as::<XString>(input),
+        "Chain" => decode_as::<Chain>(input),
+        "AssetType" => decode_as::<AssetType>(input),
+        "ApplicationState" => decode_as::<ApplicationState>(input),
+        "TradingPair" => decode_as::<TradingPair>(input),
+        "Order" => {
+            decode_as::<Order<TradingPairIndex, AccountId, Balance, Price, BlockNumber>>(input)
+        }
+        "TxType" => decode_as::<TxType>(input),
+        "EthereumAddress" => decode_as::<EthereumAddress>(input),
+        "BitcoinTrusteeType" => decode_as::<BitcoinTrusteeType>(input),
+        "BitcoinTrusteeSessionInfo" => decode_as::<BitcoinTrusteeSessionInfo<AccountId>>(input),
+        _ => {
+            if UNSUPPORTED_ARG_TYPES.lock().insert(ty.to_string()) {
+                warn!("Unsupported event argument type: {}", ty);
+            } else {
+                debug!("Unsupported event argument type: {}", ty);
+            }
+            None
+        }
+    }
+}
+
+/// Get the type parameter of the generic type, e.g. `Vec<u32>` => `u32`.
+fn strip_generic<'a>(ty: &'a str, name: &str) -> Option<&'a str> {
+    if ty.starts_with(name) && ty.ends_with('>') {
+        let inner = ty[name.len()..].trim_start();
+        if inner.starts_with('<') {
+            return Some(&inner[1..inner.len() - 1]);
+        }
+    }
+    None
+}
+
+/// Split the types of tuple by the top level commas, e.g. `A, Vec<(B, C)>` => [`A`, `Vec<(B, C)>`].
+fn split_top_level(s: &str) -> Vec<&str> {
+    let mut parts = vec![];
+    let (mut depth, mut start) = (0i32, 0);
+    for (i, c) in s.char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if !s[start..].trim().is_empty() {
+        parts.push(&s[start..]);
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_to_json() {
+        // Block #236959 of ChainX mainnet
+        let value = hex::decode("14000000000000000001000000000000020000000000000300000005000c5043586691ef8e7ebb8d7bb595a4e09263a9169d3394cc659f74b57035d5046ef0d03a00b1695b946241c43876d07d2a50dc7bd501ca5d7de52f44d1487c95fe8991880f00682e00000000000000030000000401b1695b946241c43876d07d2a50dc7bd501ca5d7de52f44d1487c95fe8991880f682e000000000000").unwrap();
+        let events = events_to_json(&value).unwrap();
+        let events = events.as_array().unwrap();
+        assert_eq!(events.len(), 5);
+        assert_eq!(
+            events[0],
+            json!({"phase":{"ApplyExtrinsic":0}, "module":"System", "event":"ExtrinsicSuccess", "args":[]})
+        );
+        assert_eq!(events[3]["phase"], json!({"ApplyExtrinsic":3}));
+        assert_eq!(events[3]["module"], "XAssets");
+        assert_eq!(events[3]["event"], "Move");
+        assert_eq!(
+            events[3]["args"],
+            json!([
+                "PCX",
+                "0x6691ef8e7ebb8d7bb595a4e09263a9169d3394cc659f74b57035d5046ef0d03a",
+                "Free",
+                "0xb1695b946241c43876d07d2a50dc7bd501ca5d7de52f44d1487c95fe8991880f",
+                "Free",
+                11880
+            ])
+        );
+        assert_eq!(events[4]["module"], "XFeeManager");
+        assert_eq!(events[4]["args"][1], 11880);
+
+        let entries =
+            split_events(&json!({"type":"value", "prefix":"System Events", "value":events}));
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[3]["type"], "event");
+        assert_eq!(entries[3]["prefix"], "XAssets.Move");
+        assert_eq!(entries[3]["key"], 3);
+    }
+
+    #[test]
+    fn test_events_to_json_unknown_event() {
+        // System.ExtrinsicSuccess, and an unknown event of module 0xff
+        let value = hex::decode("0800000000000000000000000000ff000102").unwrap();
+        let events = events_to_json(&value).unwrap();
+        assert_eq!(events[0]["event"], "ExtrinsicSuccess");
+        assert_eq!(events[1]["error"], "Unknown event, module: 255, event: 0");
+        assert_eq!(events[1]["remaining"], "0x0000000000ff000102");
+    }
+
+    #[test]
+    fn test_account_pointers() {
+        let base = || "/value/args/1".to_string();
+        assert_eq!(account_pointers("AccountId", base()), vec!["/value/args/1"]);
+        assert_eq!(
+            account_pointers("Vec<(AccountId, u32)>", base()),
+            vec!["/value/args/1/*/0"]
+        );
+        assert_eq!(
+            account_pointers("(AccountId, Option<AccountId>)", base()),
+            vec!["/value/args/1/0", "/value/args/1/1"]
+        );
+        assert!(account_pointers("Balance", base()).is_empty());
+    }
+
+    #[test]
+    fn test_events_to_json_corrupted_len() {
+        // the length (u32::MAX) isn't preallocated
+        let events = events_to_json(&[0x03, 0xff, 0xff, 0xff, 0xff, 0x00]).unwrap();
+        assert_eq!(events.as_array().map(Vec::len), Some(1));
+        assert!(events[0]["error"].is_string());
+    }
+
+    #[test]
+    fn test_decode_arg() {
+        let mut input: &[u8] = &[8, 1, 2, 0, 0, 0, 3, 4, 0, 0, 0, 0];
+        assert_eq!(
+            decode_arg("Vec<(u8, u32)>", &mut input),
+            Some(json!([[1, 2], [3, 4]]))
+        );
+        assert_eq!(decode_arg("Option<u8>", &mut input), Some(Value::Null));
+        assert!(input.is_empty());
+        assert_eq!(split_top_level("A, Vec<(B, C)>"), vec!["A", " Vec<(B, C)>"]);
+
+        // the unsupported type is warned once
+        assert_eq!(decode_arg("Vec<UnsupportedType>", &mut &[4, 0][..]), None);
+        assert_eq!(decode_arg("UnsupportedType", &mut &[0][..]), None);
+        assert!(UNSUPPORTED_ARG_TYPES.lock().contains("UnsupportedType"));
+    }
+}
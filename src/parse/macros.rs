@@ -6,17 +6,32 @@ macro_rules! to_json {
 
     ($prefix:ident, $key:ident => $k:ident, $value:ident => $v:ident) => {
         {
-            *$k = match Decode::decode(&mut $key) {
-                Some(key) => key,
-                None => {
-                    let err = format!("Decode failed, prefix: {:?}, key: {:?}", $prefix, $k);
-                    error!("Runtime storage parse error: {:?}", err);
-                    return Err(err.into());
-                }
-            };
+            *$k = decode_key!($prefix, $key => $k);
             to_json_impl!("map", $prefix, $k, $value => $v)
         }
     };
+
+    ($prefix:ident, $key:ident => ($k1:ident, $k2:ident), $value:ident => $v:ident) => {
+        {
+            *$k1 = decode_key!($prefix, $key => $k1);
+            *$k2 = decode_key!($prefix, $key => $k2);
+            let double_key = serde_json::json!([$k1, $k2]);
+            to_json_impl!("double_map", $prefix, double_key, $value => $v)
+        }
+    };
+}
+
+macro_rules! decode_key {
+    ($prefix:ident, $key:ident => $k:ident) => {
+        match Decode::decode(&mut $key) {
+            Some(key) => key,
+            None => {
+                let err = format!("Decode failed, prefix: {:?}, key: {:?}", $prefix, $k);
+                error!("Runtime storage parse error: {:?}", err);
+                return Err(err.into());
+            }
+        }
+    };
 }
 
 macro_rules! to_json_impl {
@@ -1,15 +1,23 @@
 #[macro_use]
 mod macros;
+mod bitcoin;
+mod event;
 mod primitives;
+mod schema;
 
 use std::collections::BTreeMap;
+use std::convert::TryFrom;
 
 use parity_codec::Decode;
 use strum::{EnumIter, EnumProperty, IntoEnumIterator, IntoStaticStr};
 
 use self::primitives::*;
-use crate::types::{btc, MultiNodeIndex, Node};
-use crate::Result;
+
+pub use self::bitcoin::BtcNetwork;
+pub use self::event::{account_event_prefixes, event_prefixes, load_event_registry, split_events};
+pub use self::schema::{catalog, CatalogEntry};
+use crate::types::{Linkage, MultiNodeIndex, Node};
+use crate::{Error, Result, StateStore};
 
 #[rustfmt::skip]
 #[allow(clippy::large_enum_variant)]
@@ -18,17 +26,32 @@ pub enum RuntimeStorage {
     // ============================================================================================
     // Substrate
     // ============================================================================================
+    // well known keys ----------------------------------------------------------------------------
+    #[strum(serialize = ":code", props(Type = "value"))]
+    WellKnownCode(Vec<u8>),
+    #[strum(serialize = ":heappages", props(Type = "value"))]
+    WellKnownHeapPages(u64),
+    #[strum(serialize = ":auth:len", props(Type = "value"))]
+    WellKnownAuthLen(u32),
+    #[strum(serialize = ":auth:", props(Type = "map"))]
+    WellKnownAuth(u32, SessionKey),
+    #[strum(serialize = ":changes_trie", props(Type = "value"))]
+    WellKnownChangesTrie(ChangesTrieConfiguration),
     // system -------------------------------------------------------------------------------------
-//    #[strum(serialize = "System Number", props(Type = "value"))]
-//    SystemNumber(BlockNumber),
-    #[strum(serialize = "System AccountNonce", props(Type = "map"))]
+    #[strum(serialize = "System Number", props(Type = "value"))]
+    SystemNumber(BlockNumber),
+    #[strum(serialize = "System ParentHash", props(Type = "value"))]
+    SystemParentHash(H256),
+    #[strum(serialize = "System Events", props(Type = "value"))]
+    SystemEvents(Vec<u8>),
+    #[strum(serialize = "System AccountNonce", props(Type = "map", Account = "/key"))]
     SystemAccountNonce(AccountId, Index),
     #[strum(serialize = "System BlockHash", props(Type = "map"))]
     SystemBlockHash(BlockNumber, H256),
     // indices ------------------------------------------------------------------------------------
     #[strum(serialize = "Indices NextEnumSet", props(Type = "value"))]
     IndicesNextEnumSet(AccountIndex),
-    #[strum(serialize = "Indices EnumSet", props(Type = "map"))]
+    #[strum(serialize = "Indices EnumSet", props(Type = "map", Account = "/value/*"))]
     IndicesEnumSet(AccountIndex, Vec<AccountId>),
     // timestamp ----------------------------------------------------------------------------------
     #[strum(serialize = "Timestamp Now", props(Type = "value"))]
@@ -43,7 +66,7 @@ pub enum RuntimeStorage {
     #[strum(serialize = "Timestamp ReportLatency", props(Type = "value"))]
     TimestampReportLatency(BlockNumber),
     // session ------------------------------------------------------------------------------------
-    #[strum(serialize = "Session Validators", props(Type = "value"))]
+    #[strum(serialize = "Session Validators", props(Type = "value", Account = "/value/*/0"))]
     SessionValidators(Vec<(AccountId, u64)>),
     #[strum(serialize = "Session SessionLength", props(Type = "value"))]
     SessionSessionLength(BlockNumber),
@@ -59,22 +82,22 @@ pub enum RuntimeStorage {
     // ChainX
     // ============================================================================================
     // xsystem ------------------------------------------------------------------------------------
-    #[strum(serialize = "XSystem BlockProducer", props(Type = "value"))]
+    #[strum(serialize = "XSystem BlockProducer", props(Type = "value", Account = "/value"))]
     XSystemBlockProducer(AccountId),
     #[strum(serialize = "XSystem NetworkProps", props(Type = "value"))]
     XSystemNetworkProps((NetworkType, AddressType)),
     // xaccounts ----------------------------------------------------------------------------------
-    #[strum(serialize = "XAccounts IntentionOf", props(Type = "map"))]
+    #[strum(serialize = "XAccounts IntentionOf", props(Type = "map", Account = "/value"))]
     XAccountsIntentionOf(Name, AccountId),
-    #[strum(serialize = "XAccounts IntentionNameOf", props(Type = "map"))]
+    #[strum(serialize = "XAccounts IntentionNameOf", props(Type = "map", Account = "/key"))]
     XAccountsIntentionNameOf(AccountId, Name),
-    #[strum(serialize = "XAccounts IntentionPropertiesOf", props(Type = "map"))]
+    #[strum(serialize = "XAccounts IntentionPropertiesOf", props(Type = "map", Account = "/key"))]
     XAccountsIntentionPropertiesOf(AccountId, IntentionProps<SessionKey, BlockNumber>),
-    #[strum(serialize = "XAccounts TeamAddress", props(Type = "value"))]
+    #[strum(serialize = "XAccounts TeamAddress", props(Type = "value", Account = "/value"))]
     XAccountsTeamAddress(AccountId),
-    #[strum(serialize = "XAccounts CouncilAddress", props(Type = "value"))]
+    #[strum(serialize = "XAccounts CouncilAddress", props(Type = "value", Account = "/value"))]
     XAccountsCouncilAddress(AccountId),
-    #[strum(serialize = "XAccounts BlockedAccounts", props(Type = "value"))]
+    #[strum(serialize = "XAccounts BlockedAccounts", props(Type = "value", Account = "/value/*"))]
     XAccountsBlockedAccounts(Vec<AccountId>),
     // xfee ---------------------------------------------------------------------------------------
     #[strum(serialize = "XFeeManager Switcher", props(Type = "value"))]
@@ -95,7 +118,7 @@ pub enum RuntimeStorage {
     XAssetsAssetInfo(Token, (Asset, bool, BlockNumber)),
     #[strum(serialize = "XAssets AssetLimitProps", props(Type = "map"))]
     XAssetsAssetLimitProps(Token, BTreeMap<AssetLimit, bool>),
-    #[strum(serialize = "XAssets AssetBalance", props(Type = "map"))]
+    #[strum(serialize = "XAssets AssetBalance", props(Type = "map", Account = "/key/0"))]
     XAssetsAssetBalance((AccountId, Token), BTreeMap<AssetType, Balance>),
     #[strum(serialize = "XAssets TotalAssetBalance", props(Type = "map"))]
     XAssetsTotalAssetBalance(Token, BTreeMap<AssetType, Balance>),
@@ -106,14 +129,14 @@ pub enum RuntimeStorage {
     XAssetsRecordsApplicationMHeader(Chain, MultiNodeIndex<Chain, Application<AccountId, Balance, Timestamp>>),
     #[strum(serialize = "XAssetsRecords ApplicationMTail", props(Type = "map"))]
     XAssetsRecordsApplicationMTail(Chain, MultiNodeIndex<Chain, Application<AccountId, Balance, Timestamp>>),
-    #[strum(serialize = "XAssetsRecords ApplicationMap", props(Type = "map"))]
+    #[strum(serialize = "XAssetsRecords ApplicationMap", props(Type = "map", Account = "/value/data/applicant"))]
     XAssetsRecordsApplicationMap(u32, Node<Application<AccountId, Balance, Timestamp>>),
     #[strum(serialize = "XAssetsRecords SerialNumber", props(Type = "value"))]
     XAssetsRecordsSerialNumber(u32),
     // xfisher ------------------------------------------------------------------------------------
     #[strum(serialize = "XFisher Reported", props(Type = "map"))]
     XFisherReported(H512, ()),
-    #[strum(serialize = "XFisher Fishermen", props(Type = "value"))]
+    #[strum(serialize = "XFisher Fishermen", props(Type = "value", Account = "/value/*"))]
     XFisherFishermen(Vec<AccountId>),
     // xmining ------------------------------------------------------------------------------------
     // XStaking
@@ -143,29 +166,33 @@ pub enum RuntimeStorage {
     XStakingLastEraLengthChange(BlockNumber),
     #[strum(serialize = "XStaking ForcingNewEra", props(Type = "value"))]
     XStakingForcingNewEra(()),
-    #[strum(serialize = "XStaking StakeWeight", props(Type = "map"))]
+    #[strum(serialize = "XStaking StakeWeight", props(Type = "map", Account = "/key"))]
     XStakingStakeWeight(AccountId, Balance),
-    #[strum(serialize = "XStaking Intentions", props(Type = "linked_map"))]
-    XStakingIntentions(AccountId, IntentionProfs<Balance, BlockNumber>),
-    #[strum(serialize = "XStaking IntentionsV1", props(Type = "linked_map"))]
-    XStakingIntentionsV1(AccountId, IntentionProfsV1<Balance, BlockNumber>),
-    #[strum(serialize = "XStaking NominationRecords", props(Type = "map"))]
+    #[strum(serialize = "XStaking Intentions", props(Type = "linked_map", Account = "/key"))]
+    XStakingIntentions(AccountId, (IntentionProfs<Balance, BlockNumber>, Linkage<AccountId>)),
+    #[strum(serialize = "head of XStaking Intentions", props(Type = "value", Account = "/value"))]
+    XStakingIntentionsHead(AccountId),
+    #[strum(serialize = "XStaking IntentionsV1", props(Type = "linked_map", Account = "/key"))]
+    XStakingIntentionsV1(AccountId, (IntentionProfsV1<Balance, BlockNumber>, Linkage<AccountId>)),
+    #[strum(serialize = "head of XStaking IntentionsV1", props(Type = "value", Account = "/value"))]
+    XStakingIntentionsV1Head(AccountId),
+    #[strum(serialize = "XStaking NominationRecords", props(Type = "map", Account = "/key/0,/key/1"))]
     XStakingNominationRecords((AccountId, AccountId), NominationRecord<Balance, BlockNumber>),
-    #[strum(serialize = "XStaking NominationRecordsV1", props(Type = "map"))]
+    #[strum(serialize = "XStaking NominationRecordsV1", props(Type = "map", Account = "/key/0,/key/1"))]
     XStakingNominationRecordsV1((AccountId, AccountId), NominationRecordV1<Balance, BlockNumber>),
     #[strum(serialize = "XStaking UpperBoundFactor", props(Type = "value"))]
     XStakingUpperBoundFactor(u32),
-    #[strum(serialize = "XStaking EvilValidatorsPerSession", props(Type = "value"))]
+    #[strum(serialize = "XStaking EvilValidatorsPerSession", props(Type = "value", Account = "/value/*"))]
     XStakingEvilValidatorsPerSession(Vec<AccountId>),
-    #[strum(serialize = "XStaking LastRenominationOf", props(Type = "map"))]
+    #[strum(serialize = "XStaking LastRenominationOf", props(Type = "map", Account = "/key"))]
     XStakingLastRenominationOf(AccountId, BlockNumber),
     #[strum(serialize = "XStaking MaxUnbondEntriesPerIntention", props(Type = "value"))]
     XStakingMaxUnbondEntriesPerIntention(u32),
     #[strum(serialize = "XStaking MinimumPenalty", props(Type = "value"))]
     XStakingMinimumPenalty(Balance),
-    #[strum(serialize = "XStaking OfflineValidatorsPerSession", props(Type = "value"))]
+    #[strum(serialize = "XStaking OfflineValidatorsPerSession", props(Type = "value", Account = "/value/*"))]
     XStakingOfflineValidatorsPerSession(Vec<AccountId>),
-    #[strum(serialize = "XStaking MissedOfPerSession", props(Type = "map"))]
+    #[strum(serialize = "XStaking MissedOfPerSession", props(Type = "map", Account = "/key"))]
     XStakingMissedOfPerSession(AccountId, u32),
     #[strum(serialize = "XStaking MissedBlockSeverity", props(Type = "value"))]
     XStakingMissedBlockSeverity(u32),
@@ -176,28 +203,28 @@ pub enum RuntimeStorage {
     XTokensPseduIntentions(Vec<Token>),
     #[strum(serialize = "XTokens ClaimRestrictionOf", props(Type = "map"))]
     XTokensClaimRestrictionOf(Token, (u32, BlockNumber)),
-    #[strum(serialize = "XTokens LastClaimOf", props(Type = "map"))]
+    #[strum(serialize = "XTokens LastClaimOf", props(Type = "map", Account = "/key/0"))]
     XTokensLastClaimOf((AccountId, Token), BlockNumber),
     #[strum(serialize = "XTokens PseduIntentionProfiles", props(Type = "map"))]
     XTokensPseduIntentionProfiles(Token, PseduIntentionVoteWeight<BlockNumber>),
     #[strum(serialize = "XTokens PseduIntentionProfilesV1", props(Type = "map"))]
     XTokensPseduIntentionProfilesV1(Token, PseduIntentionVoteWeightV1<BlockNumber>),
-    #[strum(serialize = "XTokens DepositRecords", props(Type = "map"))]
+    #[strum(serialize = "XTokens DepositRecords", props(Type = "map", Account = "/key/0"))]
     XTokensDepositRecords((AccountId, Token), DepositVoteWeight<BlockNumber>),
-    #[strum(serialize = "XTokens DepositRecordsV1", props(Type = "map"))]
+    #[strum(serialize = "XTokens DepositRecordsV1", props(Type = "map", Account = "/key/0"))]
     XTokensDepositRecordsV1((AccountId, Token), DepositVoteWeightV1<BlockNumber>),
     #[strum(serialize = "XTokens DepositReward", props(Type = "value"))]
     XTokensDepositReward(Balance),
     // xmultisig ----------------------------------------------------------------------------------
-    #[strum(serialize = "XMultiSig RootAddrList", props(Type = "value"))]
+    #[strum(serialize = "XMultiSig RootAddrList", props(Type = "value", Account = "/value/*"))]
     XMultiSigRootAddrList(Vec<AccountId>),
-    #[strum(serialize = "XMultiSig MultiSigAddrInfo", props(Type = "map"))]
+    #[strum(serialize = "XMultiSig MultiSigAddrInfo", props(Type = "map", Account = "/key,/value/owner_list/*/0"))]
     XMultiSigMultiSigAddrInfo(AccountId, AddrInfo<AccountId>),
-    #[strum(serialize = "XMultiSig PendingListFor", props(Type = "map"))]
+    #[strum(serialize = "XMultiSig PendingListFor", props(Type = "map", Account = "/key"))]
     XMultiSigPendingListFor(AccountId, Vec<H256>),
-    #[strum(serialize = "XMultiSig MultiSigListItemFor", props(Type = "map"))]
+    #[strum(serialize = "XMultiSig MultiSigListItemFor", props(Type = "map", Account = "/key/0,/value"))]
     XMultiSigMultiSigListItemFor((AccountId, u32), AccountId),
-    #[strum(serialize = "XMultiSig MultiSigListLenFor", props(Type = "map"))]
+    #[strum(serialize = "XMultiSig MultiSigListLenFor", props(Type = "map", Account = "/key"))]
     XMultiSigMultiSigListLenFor(AccountId, u32),
     // xdex ---------------------------------------------------------------------------------------
     // XSpot
@@ -209,11 +236,11 @@ pub enum RuntimeStorage {
     XSpotTradingPairInfoOf(TradingPairIndex, (Price, Price, BlockNumber)),
     #[strum(serialize = "XSpot TradeHistoryIndexOf", props(Type = "map"))]
     XSpotTradeHistoryIndexOf(TradingPairIndex, TradeHistoryIndex),
-    #[strum(serialize = "XSpot OrderCountOf", props(Type = "map"))]
+    #[strum(serialize = "XSpot OrderCountOf", props(Type = "map", Account = "/key"))]
     XSpotOrderCountOf(AccountId, OrderIndex),
-    #[strum(serialize = "XSpot OrderInfoOf", props(Type = "map"))]
+    #[strum(serialize = "XSpot OrderInfoOf", props(Type = "map", Account = "/key/0,/value/props/0"))]
     XSpotOrderInfoOf((AccountId, OrderIndex), Order<TradingPairIndex, AccountId, Balance, Price, BlockNumber>),
-    #[strum(serialize = "XSpot QuotationsOf", props(Type = "map"))]
+    #[strum(serialize = "XSpot QuotationsOf", props(Type = "map", Account = "/value/*/0"))]
     XSpotQuotationsOf((TradingPairIndex, Price), Vec<(AccountId, OrderIndex)>),
     #[strum(serialize = "XSpot HandicapOf", props(Type = "map"))]
     XSpotHandicapOf(TradingPairIndex, Handicap<Price>),
@@ -221,7 +248,7 @@ pub enum RuntimeStorage {
     XSpotPriceVolatility(u32),
     // xbridge ------------------------------------------------------------------------------------
     // common
-    #[strum(serialize = "XBridgeCommon CrossChainBinding", props(Type = "map"))]
+    #[strum(serialize = "XBridgeCommon CrossChainBinding", props(Type = "map", Account = "/key/1,/value"))]
     XBridgeCommonCrossChainBinding((Token, AccountId), AccountId),
     // BTC
     #[strum(serialize = "XBridgeOfBTC BestIndex", props(Type = "value"))]
@@ -235,13 +262,13 @@ pub enum RuntimeStorage {
     #[strum(serialize = "XBridgeOfBTC TxMarkFor", props(Type = "map"))]
     XBridgeOfBTCTxMarkFor(H256, ()),
     #[strum(serialize = "XBridgeOfBTC InputAddrFor", props(Type = "map"))]
-    XBridgeOfBTCInputAddrFor(H256, btc::Address),
+    XBridgeOfBTCInputAddrFor(H256, bitcoin::Address),
     #[strum(serialize = "XBridgeOfBTC PendingDepositMap", props(Type = "map"))]
-    XBridgeOfBTCPendingDepositMap(btc::Address, Vec<DepositCache>),
-    #[strum(serialize = "XBridgeOfBTC CurrentWithdrawalProposal", props(Type = "value"))]
+    XBridgeOfBTCPendingDepositMap(bitcoin::Address, Vec<DepositCache>),
+    #[strum(serialize = "XBridgeOfBTC CurrentWithdrawalProposal", props(Type = "value", Account = "/value/trustee_list/*/0"))]
     XBridgeOfBTCCurrentWithdrawalProposal(WithdrawalProposal<AccountId>),
     #[strum(serialize = "XBridgeOfBTC GenesisInfo", props(Type = "value"))]
-    XBridgeOfBTCGenesisInfo((btc::BlockHeader, u32)),
+    XBridgeOfBTCGenesisInfo((bitcoin::BlockHeader, u32)),
     #[strum(serialize = "XBridgeOfBTC ParamsInfo", props(Type = "value"))]
     XBridgeOfBTCParamsInfo(Params),
     #[strum(serialize = "XBridgeOfBTC NetworkId", props(Type = "value"))]
@@ -257,10 +284,10 @@ pub enum RuntimeStorage {
     #[strum(serialize = "XBridgeOfBTC MaxWithdrawalCount", props(Type = "value"))]
     XBridgeOfBTCMaxWithdrawalCount(u32),
     // BTC lockup
-    #[strum(serialize = "XBridgeOfBTCLockup LockedUpBTC", props(Type = "map"))]
-    XBridgeOfBTCLockupLockedUpBTC((H256, u32), (AccountId, u64, btc::Address)),
+    #[strum(serialize = "XBridgeOfBTCLockup LockedUpBTC", props(Type = "map", Account = "/value/0"))]
+    XBridgeOfBTCLockupLockedUpBTC((H256, u32), (AccountId, u64, bitcoin::Address)),
     #[strum(serialize = "XBridgeOfBTCLockup AddressLockedCoin", props(Type = "map"))]
-    XBridgeOfBTCLockupAddressLockedCoin(btc::Address, u64),
+    XBridgeOfBTCLockupAddressLockedCoin(bitcoin::Address, u64),
     #[strum(serialize = "XBridgeOfBTCLockup LockedCoinLimit", props(Type = "value"))]
     XBridgeOfBTCLockupLockedCoinLimit((u64, u64)),
     // SDOT
@@ -269,42 +296,132 @@ pub enum RuntimeStorage {
     #[strum(serialize = "XBridgeOfSDOT Total", props(Type = "value"))]
     XBridgeOfSDOTTotal(Balance),
     // Features
-    #[strum(serialize = "XBridgeFeatures TrusteeMultiSigAddr", props(Type = "map"))]
+    #[strum(serialize = "XBridgeFeatures TrusteeMultiSigAddr", props(Type = "map", Account = "/value"))]
     XBridgeFeaturesTrusteeMultiSigAddr(Chain, AccountId),
     #[strum(serialize = "XBridgeFeatures TrusteeInfoConfigOf", props(Type = "map"))]
     XBridgeFeaturesTrusteeInfoConfigOf(Chain, TrusteeInfoConfig),
     #[strum(serialize = "XBridgeFeatures TrusteeSessionInfoLen", props(Type = "map"))]
     XBridgeFeaturesTrusteeSessionInfoLen(Chain, u32),
-    #[strum(serialize = "XBridgeFeatures BitcoinTrusteeSessionInfoOf", props(Type = "map"))]
+    #[strum(serialize = "XBridgeFeatures BitcoinTrusteeSessionInfoOf", props(Type = "map", Account = "/value/trustee_list/*"))]
     XBridgeFeaturesBitcoinTrusteeSessionInfoOf(u32, BitcoinTrusteeSessionInfo<AccountId>),
-    #[strum(serialize = "XBridgeFeatures BitcoinTrusteeIntentionPropertiesOf", props(Type = "map"))]
+    #[strum(serialize = "XBridgeFeatures BitcoinTrusteeIntentionPropertiesOf", props(Type = "map", Account = "/key"))]
     XBridgeFeaturesBitcoinTrusteeIntentionPropertiesOf(AccountId, BitcoinTrusteeIntentionProps),
-    #[strum(serialize = "XBridgeFeatures BitcoinCrossChainBinding", props(Type = "map"))]
-    XBridgeFeaturesBitcoinCrossChainBinding(AccountId, Vec<btc::Address>),
-    #[strum(serialize = "XBridgeFeatures BitcoinCrossChainOf", props(Type = "map"))]
-    XBridgeFeaturesBitcoinCrossChainOf(btc::Address, (AccountId, Option<AccountId>)),
-    #[strum(serialize = "XBridgeFeatures EthereumCrossChainBinding", props(Type = "map"))]
+    #[strum(serialize = "XBridgeFeatures BitcoinCrossChainBinding", props(Type = "map", Account = "/key"))]
+    XBridgeFeaturesBitcoinCrossChainBinding(AccountId, Vec<bitcoin::Address>),
+    #[strum(serialize = "XBridgeFeatures BitcoinCrossChainOf", props(Type = "map", Account = "/value/0,/value/1"))]
+    XBridgeFeaturesBitcoinCrossChainOf(bitcoin::Address, (AccountId, Option<AccountId>)),
+    #[strum(serialize = "XBridgeFeatures EthereumCrossChainBinding", props(Type = "map", Account = "/key"))]
     XBridgeFeaturesEthereumCrossChainBinding(AccountId, Vec<EthereumAddress>),
-    #[strum(serialize = "XBridgeFeatures EthereumCrossChainOf", props(Type = "map"))]
+    #[strum(serialize = "XBridgeFeatures EthereumCrossChainOf", props(Type = "map", Account = "/value/0,/value/1"))]
     XBridgeFeaturesEthereumCrossChainOf(EthereumAddress, (AccountId, Option<AccountId>)),
 }
 
+/// The pseudo prefix used to subscribe the storage entries whose key can't be recognized.
+pub const UNKNOWN_PREFIX: &str = "$unknown";
+/// The pseudo prefix used to subscribe the storage entries that fail to be decoded.
+pub const ERROR_PREFIX: &str = "$error";
+/// The common prefix of the Substrate well-known keys.
+const WELL_KNOWN_KEY_PREFIX: &[u8] = b":";
+/// The prefix of the bitcoin network id of chain.
+const BTC_NETWORK_ID_PREFIX: &str = "XBridgeOfBTC NetworkId";
+
 impl RuntimeStorage {
+    /// The prefixes of all runtime storages.
+    pub fn prefixes() -> Vec<&'static str> {
+        Self::iter().map(|storage| (&storage).into()).collect()
+    }
+
+    /// The prefixes of runtime storages that carry `AccountId`, with the JSON pointers (to the entry)
+    /// of the `AccountId`s, whose `*` segment matches every element of array, e.g. `/value/*/0`.
+    pub fn account_prefixes() -> Vec<(&'static str, Vec<&'static str>)> {
+        Self::iter()
+            .filter_map(|storage| {
+                let pointers = storage.get_str("Account")?.split(',').collect();
+                Some(((&storage).into(), pointers))
+            })
+            .collect()
+    }
+
+    /// Parse the storage entry, the bitcoin addresses are rendered for the network.
+    pub fn parse_with_network(
+        key: &[u8],
+        value: Vec<u8>,
+        network: BtcNetwork,
+    ) -> Result<(&'static str, serde_json::Value)> {
+        bitcoin::with_network(network, || Self::parse(key, value))
+    }
+
+    /// Parse the storage entry, the bitcoin addresses are rendered for the mainnet.
     pub fn parse(key: &[u8], value: Vec<u8>) -> Result<(&'static str, serde_json::Value)> {
-        for mut storage in Self::iter() {
+        // Match the longest prefix, e.g. `XStaking IntentionsV1` instead of `XStaking Intentions`.
+        let storage = Self::iter()
+            .filter(|storage| key.starts_with(<&'static str>::from(storage).as_bytes()))
+            .max_by_key(|storage| <&'static str>::from(storage).len());
+        if let Some(mut storage) = storage {
             let prefix: &'static str = (&storage).into();
-            if key.starts_with(prefix.as_bytes()) {
-                let json = storage.decode_by_type(&prefix, key, value)?;
-                return Ok((prefix, json));
-            }
+            let json = storage.decode_by_type(&prefix, key, value)?;
+            return Ok((prefix, json));
         }
         debug!("Runtime storage parse: No matching key found");
         Err("No matching key found".into())
     }
 
+    /// The bitcoin network of chain that the parsed entry of `XBridgeOfBTC NetworkId` switches to.
+    pub fn btc_network(json: &serde_json::Value) -> Option<BtcNetwork> {
+        if json["prefix"] != BTC_NETWORK_ID_PREFIX {
+            return None;
+        }
+        btc_network_of_id(&json["value"])
+    }
+
+    /// The bitcoin network of chain that has been applied into the state store.
+    pub fn stored_btc_network(store: &StateStore) -> Option<BtcNetwork> {
+        btc_network_of_id(
+            &store
+                .get(BTC_NETWORK_ID_PREFIX, &serde_json::Value::Null)?
+                .value,
+        )
+    }
+
+    /// Build the json of the storage entry that can't be parsed, so that it can be forwarded
+    /// instead of being dropped.
+    ///
+    /// The type is `error` when the key matches a known prefix but the decoding fails,
+    /// otherwise it's `unknown` and the prefix is guessed from the readable part of the key.
+    /// The Substrate well-known keys (e.g. `:code`, whose value is the whole runtime) aren't forwarded.
+    pub fn passthrough(
+        key: &[u8],
+        value: &[u8],
+        err: &Error,
+    ) -> Option<(String, serde_json::Value)> {
+        if key.starts_with(WELL_KNOWN_KEY_PREFIX) {
+            return None;
+        }
+        let (type_, prefix) = match Self::match_prefix(key) {
+            Some(prefix) => ("error", prefix.to_string()),
+            None => ("unknown", guess_prefix(key)),
+        };
+        let json = serde_json::json!({
+            "type": type_,
+            "prefix": prefix,
+            "key": format!("0x{}", hex::encode(key)),
+            "value": format!("0x{}", hex::encode(value)),
+            "error": err.to_string(),
+        });
+        Some((prefix, json))
+    }
+
+    /// Get the prefix of runtime storage that matches the key.
+    fn match_prefix(key: &[u8]) -> Option<&'static str> {
+        Self::iter()
+            .map(|storage| -> &'static str { (&storage).into() })
+            .filter(|prefix| key.starts_with(prefix.as_bytes()))
+            .max_by_key(|prefix| prefix.len())
+    }
+
     fn match_key<'a>(&self, prefix: &str, key: &'a [u8]) -> Result<&'a [u8]> {
         let key = match self.get_str("Type") {
-            Some("map") | Some("linked_map") => &key[prefix.len()..],
+            Some("map") | Some("linked_map") | Some("double_map") => &key[prefix.len()..],
             Some("value") => key,
             _ => {
                 error!("Runtime storage parse: get storage type failed");
@@ -322,6 +439,14 @@ impl RuntimeStorage {
 
         match self {
             // Substrate ==========================================================================
+            WellKnownCode(_) => Ok(code_to_json(prefix, &value)),
+            WellKnownHeapPages(ref mut v) => to_json!(prefix, value => v),
+            WellKnownAuthLen(ref mut v) => to_json!(prefix, value => v),
+            WellKnownAuth(ref mut k, ref mut v) => to_json!(prefix, key => k, value => v),
+            WellKnownChangesTrie(ref mut v) => to_json!(prefix, value => v),
+            SystemNumber(ref mut v) => to_json!(prefix, value => v),
+            SystemParentHash(ref mut v) => to_json!(prefix, value => v),
+            SystemEvents(_) => events_to_json(prefix, &value),
             SystemAccountNonce(ref mut k, ref mut v) => to_json!(prefix, key => k, value => v),
             SystemBlockHash(ref mut k, ref mut v) => to_json!(prefix, key => k, value => v),
             IndicesNextEnumSet(ref mut v) => to_json!(prefix, value => v),
@@ -384,8 +509,10 @@ impl RuntimeStorage {
             XStakingLastEraLengthChange(ref mut v) => to_json!(prefix, value => v),
             XStakingForcingNewEra(ref mut v) => to_json!(prefix, value => v),
             XStakingStakeWeight(ref mut k, ref mut v) => to_json!(prefix, key => k, value => v),
-            XStakingIntentions(ref mut k, ref mut v) => to_json!(prefix, key => k, value => v),
-            XStakingIntentionsV1(ref mut k, ref mut v) => to_json!(prefix, key => k, value => v),
+            XStakingIntentions(ref mut k, ref mut v) => to_json!(prefix, key => k, value => v).map(split_linkage),
+            XStakingIntentionsHead(ref mut v) => to_json!(prefix, value => v),
+            XStakingIntentionsV1(ref mut k, ref mut v) => to_json!(prefix, key => k, value => v).map(split_linkage),
+            XStakingIntentionsV1Head(ref mut v) => to_json!(prefix, value => v),
             XStakingNominationRecords(ref mut k, ref mut v) => to_json!(prefix, key => k, value => v),
             XStakingNominationRecordsV1(ref mut k, ref mut v) => to_json!(prefix, key => k, value => v),
             XStakingUpperBoundFactor(ref mut v) => to_json!(prefix, value => v),
@@ -435,7 +562,13 @@ impl RuntimeStorage {
             XBridgeOfBTCCurrentWithdrawalProposal(ref mut v) => to_json!(prefix, value => v),
             XBridgeOfBTCGenesisInfo(ref mut v) => to_json!(prefix, value => v),
             XBridgeOfBTCParamsInfo(ref mut v) => to_json!(prefix, value => v),
-            XBridgeOfBTCNetworkId(ref mut v) => to_json!(prefix, value => v),
+            XBridgeOfBTCNetworkId(ref mut v) => {
+                let json = to_json!(prefix, value => v);
+                if BtcNetwork::from_id(*v).is_none() {
+                    warn!("Unknown bitcoin network id of chain: {}", v);
+                }
+                json
+            }
             XBridgeOfBTCReservedBlock(ref mut v) => to_json!(prefix, value => v),
             XBridgeOfBTCConfirmationNumber(ref mut v) => to_json!(prefix, value => v),
             XBridgeOfBTCBtcWithdrawalFee(ref mut v) => to_json!(prefix, value => v),
@@ -462,6 +595,90 @@ impl RuntimeStorage {
     }
 }
 
+/// The events are decoded by the event registry, see `event` module for details.
+fn events_to_json(prefix: &str, value: &[u8]) -> Result<serde_json::Value> {
+    if value.is_empty() {
+        return Ok(build_json!("value", prefix, null, null));
+    }
+    match event::events_to_json(value) {
+        Some(events) => Ok(build_json!("value", prefix, null, events)),
+        None => {
+            let err = format!("Decode failed, prefix: {:?}", prefix);
+            error!("Runtime storage parse error: {:?}", err);
+            Err(err.into())
+        }
+    }
+}
+
+fn btc_network_of_id(id: &serde_json::Value) -> Option<BtcNetwork> {
+    BtcNetwork::from_id(u32::try_from(id.as_u64()?).ok()?)
+}
+
+/// The value of `linked_map` is stored with its `Linkage`, which is split into the `linkage` field,
+/// so that the value keeps the shape of the plain map.
+fn split_linkage(mut json: serde_json::Value) -> serde_json::Value {
+    if let Some(value) = json["value"].as_array().cloned() {
+        if let [value, linkage] = value.as_slice() {
+            json["value"] = value.clone();
+            json["linkage"] = linkage.clone();
+        }
+    }
+    json
+}
+
+/// The runtime code (`:code`) is stored as the raw wasm blob, which is reported as
+/// `runtime_upgrade` with the hash and size of the blob instead of the blob itself.
+fn code_to_json(prefix: &str, code: &[u8]) -> serde_json::Value {
+    let code = if code.is_empty() {
+        serde_json::Value::Null
+    } else {
+        let hash = blake2_rfc::blake2b::blake2b(32, &[], code);
+        serde_json::json!({
+            "hash": format!("0x{}", hex::encode(hash.as_bytes())),
+            "size": code.len(),
+        })
+    };
+    build_json!("runtime_upgrade", prefix, null, code)
+}
+
+/// Guess the prefix (`Module Item`) from the readable part of the unknown key.
+/// Fall back to the hex of the leading bytes if nothing readable is found.
+fn guess_prefix(key: &[u8]) -> String {
+    let readable = key
+        .iter()
+        .take_while(|&&b| b.is_ascii_graphic() || b == b' ')
+        .map(|&b| b as char)
+        .collect::<String>();
+    if readable.is_empty() {
+        return format!("0x{}", hex::encode(&key[..key.len().min(16)]));
+    }
+    let mut words = readable.splitn(2, ' ');
+    match (words.next(), words.next()) {
+        (Some(module), Some(item)) if !module.is_empty() => {
+            let item = item
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || *c == '_')
+                .collect::<String>();
+            format!("{} {}", module, item)
+        }
+        _ => readable.clone(),
+    }
+}
+
+/// Normalize the address given by users into the form of the decoded keys,
+/// i.e. SS58 address => `0x` hex of the public key, Ethereum address => EIP-55 form.
+pub fn normalize_address(s: &str) -> Option<String> {
+    if let Some(public) = from_ss58check(s) {
+        return Some(format!("0x{}", hex::encode(public.0)));
+    }
+    if s.starts_with("0x") {
+        if let Some(address) = EthereumAddress::from_checksum_str(s) {
+            return Some(address.to_checksum_str());
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -532,6 +749,61 @@ mod tests {
         assert_eq!(got, exp);
     }
 
+    #[test]
+    fn test_parse_match_map_ethereum_address() {
+        let mut key = b"XBridgeOfSDOT Claims".to_vec();
+        key.extend(hex::decode("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap());
+        let value = hex::decode("e803000000000000").unwrap();
+        let (_, got) = RuntimeStorage::parse(&key, value).unwrap();
+        let exp = serde_json::Value::from_str(
+            r#"{
+                "type":"map",
+                "prefix":"XBridgeOfSDOT Claims",
+                "key":"0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+                "value":1000
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(got, exp);
+
+        let address = EthereumAddress::from_checksum_str(got["key"].as_str().unwrap());
+        assert!(address.is_some());
+        assert!(
+            EthereumAddress::from_checksum_str("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed")
+                .is_some()
+        );
+        assert!(
+            EthereumAddress::from_checksum_str("0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_parse_match_well_known_keys() {
+        let (_, got) = RuntimeStorage::parse(b":code", vec![0x00, 0x61, 0x73, 0x6d]).unwrap();
+        assert_eq!(got["type"], "runtime_upgrade");
+        assert_eq!(got["prefix"], ":code");
+        assert_eq!(got["value"]["size"], 4);
+        assert_eq!(
+            got["value"]["hash"],
+            format!(
+                "0x{}",
+                hex::encode(blake2_rfc::blake2b::blake2b(32, &[], b"\0asm").as_bytes())
+            )
+        );
+
+        let (prefix, got) = RuntimeStorage::parse(b":auth:len", vec![4, 0, 0, 0]).unwrap();
+        assert_eq!(prefix, ":auth:len");
+        assert_eq!(got["value"], 4);
+
+        let mut key = b":auth:".to_vec();
+        key.extend(&[1, 0, 0, 0]);
+        let (prefix, got) = RuntimeStorage::parse(&key, vec![1; 32]).unwrap();
+        assert_eq!(prefix, ":auth:");
+        assert_eq!(got["key"], 1);
+        assert_eq!(got["value"], format!("0x{}", hex::encode([1u8; 32])));
+    }
+
     #[test]
     fn test_parse_match_btree_map() {
         let key = "XAssets TotalAssetBalance\u{c}BTC".as_bytes();
@@ -566,6 +838,116 @@ mod tests {
         assert_eq!(got, exp);
     }
 
+    #[test]
+    fn test_parse_match_longest_prefix() {
+        let mut key = b"XStaking IntentionsV1".to_vec();
+        key.extend_from_slice(&[1; 32]);
+        let mut value = vec![0; 8 + 16 + 8];
+        // linkage: previous = None, next = Some([2; 32])
+        value.extend_from_slice(&[0, 1]);
+        value.extend_from_slice(&[2; 32]);
+        let (prefix, got) = RuntimeStorage::parse(&key, value).unwrap();
+        assert_eq!(prefix, "XStaking IntentionsV1");
+        assert_eq!(got["type"], "map");
+        assert!(got["value"].is_object());
+        assert_eq!(got["linkage"]["previous"], serde_json::Value::Null);
+        assert_eq!(
+            got["linkage"]["next"],
+            format!("0x{}", hex::encode([2; 32]))
+        );
+    }
+
+    #[test]
+    fn test_parse_double_map() {
+        fn decode(mut key: &[u8], value: Vec<u8>) -> Result<serde_json::Value> {
+            let prefix = "Module DoubleMap";
+            let (mut k1, mut k2, mut v) = (0u32, 0u64, 0u64);
+            let (k1, k2, v) = (&mut k1, &mut k2, &mut v);
+            to_json!(prefix, key => (k1, k2), value => v)
+        }
+
+        // key: (7u32, 9u64), value: 100u64
+        let key = [7, 0, 0, 0, 9, 0, 0, 0, 0, 0, 0, 0];
+        let value = vec![100, 0, 0, 0, 0, 0, 0, 0];
+        assert_eq!(
+            decode(&key, value.clone()).unwrap(),
+            serde_json::json!({
+                "type": "double_map",
+                "prefix": "Module DoubleMap",
+                "key": [7, 9],
+                "value": 100,
+            })
+        );
+        // the second part of key is missing
+        assert!(decode(&key[..4], value).is_err());
+        // the entry has been removed
+        assert_eq!(
+            decode(&key, vec![]).unwrap()["value"],
+            serde_json::Value::Null
+        );
+    }
+
+    #[test]
+    fn test_btc_network() {
+        let key = "XBridgeOfBTC NetworkId".as_bytes();
+        let (_, testnet) = RuntimeStorage::parse(key, vec![1, 0, 0, 0]).unwrap();
+        assert_eq!(
+            RuntimeStorage::btc_network(&testnet),
+            Some(BtcNetwork::Testnet)
+        );
+        let (_, unknown) = RuntimeStorage::parse(key, vec![2, 0, 0, 0]).unwrap();
+        assert_eq!(RuntimeStorage::btc_network(&unknown), None);
+        let key = "XBridgeOfBTC BestIndex".as_bytes();
+        let (_, other) = RuntimeStorage::parse(key, vec![1; 32]).unwrap();
+        assert_eq!(RuntimeStorage::btc_network(&other), None);
+
+        // the network id is resumed from the state store
+        let mut store = StateStore::new();
+        assert_eq!(RuntimeStorage::stored_btc_network(&store), None);
+        store.apply_block(0, &[testnet]).unwrap();
+        assert_eq!(
+            RuntimeStorage::stored_btc_network(&store),
+            Some(BtcNetwork::Testnet)
+        );
+    }
+
+    #[test]
+    fn test_parse_passthrough() {
+        let key = "System ExtrinsicsRoot".as_bytes();
+        let value = vec![0x04, 0x00];
+        let err = RuntimeStorage::parse(key, value.clone()).unwrap_err();
+        let (prefix, got) = RuntimeStorage::passthrough(key, &value, &err).unwrap();
+        assert_eq!(prefix, "System ExtrinsicsRoot");
+        assert_eq!(got["type"], "unknown");
+        assert_eq!(got["key"], format!("0x{}", hex::encode(key)));
+        assert_eq!(got["value"], "0x0400");
+
+        let key = "XAssets AssetList\x00".as_bytes();
+        let value = vec![0x04];
+        let err = RuntimeStorage::parse(key, value.clone()).unwrap_err();
+        let (prefix, got) = RuntimeStorage::passthrough(key, &value, &err).unwrap();
+        assert_eq!(prefix, "XAssets AssetList");
+        assert_eq!(got["type"], "error");
+        assert_eq!(got["value"], "0x04");
+
+        // the well-known keys
+        for key in &[&b":code"[..], &b":extrinsic_index"[..]] {
+            let value = vec![0x00; 4];
+            let err = RuntimeStorage::parse(key, value.clone())
+                .err()
+                .unwrap_or_else(|| "Invalid value".into());
+            assert_eq!(RuntimeStorage::passthrough(key, &value, &err), None);
+        }
+    }
+
+    #[test]
+    fn test_guess_prefix() {
+        assert_eq!(guess_prefix(b"System Number"), "System Number");
+        assert_eq!(guess_prefix(b"XAssets Foo\x0cPCX"), "XAssets Foo");
+        assert_eq!(guess_prefix(b":code"), ":code");
+        assert_eq!(guess_prefix(&[0xff, 0x01]), "0xff01");
+    }
+
     #[test]
     fn test_parse_btc_block_header_for() {
         let key: Vec<u8> = vec![
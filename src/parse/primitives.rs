@@ -2,7 +2,10 @@
 
 use parity_codec::{Codec, Decode, Encode};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
 
+use super::bitcoin;
+use crate::types::schema::{self, field, object, tuple, variants, Schema};
 use crate::types::{btc, Bytes, NodeT};
 
 // ================================================================================================
@@ -76,6 +79,12 @@ impl<'de> Deserialize<'de> for Public {
     }
 }
 
+impl Schema for Public {
+    fn schema() -> Value {
+        schema::hex(Some(32))
+    }
+}
+
 const PREFIX: &[u8] = b"SS58PRE";
 
 fn ss58hash(data: &[u8]) -> blake2_rfc::blake2b::Blake2bResult {
@@ -94,6 +103,39 @@ fn to_ss58check(data: impl AsRef<[u8]>) -> String {
     bs58::encode(v).into_string()
 }
 
+/// Decode the public key from the ss58check string (any address type).
+pub fn from_ss58check(s: &str) -> Option<Public> {
+    let v = bs58::decode(s).into_vec().ok()?;
+    if v.len() != 35 {
+        return None;
+    }
+    let r = ss58hash(&v[0..33]);
+    if v[33..35] != r.as_bytes()[0..2] {
+        return None;
+    }
+    let mut public = [0u8; 32];
+    public.copy_from_slice(&v[1..33]);
+    Some(Public(public))
+}
+
+/// Substrate changes trie configuration (the value of `:changes_trie`).
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+#[derive(Encode, Decode)]
+#[derive(Serialize, Deserialize)]
+pub struct ChangesTrieConfiguration {
+    pub digest_interval: u64,
+    pub digest_levels: u32,
+}
+
+impl Schema for ChangesTrieConfiguration {
+    fn schema() -> Value {
+        object(vec![
+            ("digest_interval", field(|v: &Self| &v.digest_interval)),
+            ("digest_levels", field(|v: &Self| &v.digest_levels)),
+        ])
+    }
+}
+
 // ================================================================================================
 // ChainX primitives.
 // ================================================================================================
@@ -131,6 +173,12 @@ pub enum NetworkType {
     Testnet,
 }
 
+impl Schema for NetworkType {
+    fn schema() -> Value {
+        variants(&[NetworkType::Mainnet, NetworkType::Testnet])
+    }
+}
+
 impl Default for NetworkType {
     fn default() -> Self {
         NetworkType::Testnet
@@ -164,6 +212,26 @@ where
     pub last_inactive_since: BlockNumber,
 }
 
+impl<SessionKey, BlockNumber> Schema for IntentionProps<SessionKey, BlockNumber>
+where
+    SessionKey: Clone + Default + Codec + Schema,
+    BlockNumber: Copy + Default + Codec + Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            ("url", field(|v: &Self| &v.url)),
+            ("is_active", field(|v: &Self| &v.is_active)),
+            ("about", field(|v: &Self| &v.about)),
+            ("session_key", field(|v: &Self| &v.session_key)),
+            ("registered_at", field(|v: &Self| &v.registered_at)),
+            (
+                "last_inactive_since",
+                field(|v: &Self| &v.last_inactive_since),
+            ),
+        ])
+    }
+}
+
 // ============================================================================
 // xfee/manager types.
 // ============================================================================
@@ -180,6 +248,19 @@ pub enum CallSwitcher {
     Contracts,
 }
 
+impl Schema for CallSwitcher {
+    fn schema() -> Value {
+        variants(&[
+            CallSwitcher::Global,
+            CallSwitcher::Spot,
+            CallSwitcher::XBTC,
+            CallSwitcher::XBTCLockup,
+            CallSwitcher::SDOT,
+            CallSwitcher::Contracts,
+        ])
+    }
+}
+
 impl Default for CallSwitcher {
     fn default() -> Self {
         CallSwitcher::Global
@@ -203,6 +284,12 @@ pub enum Chain {
     Ethereum,
 }
 
+impl Schema for Chain {
+    fn schema() -> Value {
+        variants(&[Chain::ChainX, Chain::Bitcoin, Chain::Ethereum])
+    }
+}
+
 impl Default for Chain {
     fn default() -> Self {
         Chain::ChainX
@@ -220,6 +307,18 @@ pub struct Asset {
     desc: Desc,
 }
 
+impl Schema for Asset {
+    fn schema() -> Value {
+        object(vec![
+            ("token", field(|v: &Self| &v.token)),
+            ("token_name", field(|v: &Self| &v.token_name)),
+            ("chain", field(|v: &Self| &v.chain)),
+            ("precision", field(|v: &Self| &v.precision)),
+            ("desc", field(|v: &Self| &v.desc)),
+        ])
+    }
+}
+
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 #[derive(Encode, Decode)]
 #[derive(Serialize, Deserialize)]
@@ -234,6 +333,21 @@ pub enum AssetType {
     GasPayment,
 }
 
+impl Schema for AssetType {
+    fn schema() -> Value {
+        variants(&[
+            AssetType::Free,
+            AssetType::ReservedStaking,
+            AssetType::ReservedStakingRevocation,
+            AssetType::ReservedWithdrawal,
+            AssetType::ReservedDexSpot,
+            AssetType::ReservedDexFuture,
+            AssetType::ReservedCurrency,
+            AssetType::GasPayment,
+        ])
+    }
+}
+
 impl Default for AssetType {
     fn default() -> Self {
         AssetType::Free
@@ -252,6 +366,19 @@ pub enum AssetLimit {
     CanDestroyFree,
 }
 
+impl Schema for AssetLimit {
+    fn schema() -> Value {
+        variants(&[
+            AssetLimit::CanMove,
+            AssetLimit::CanTransfer,
+            AssetLimit::CanDeposit,
+            AssetLimit::CanWithdraw,
+            AssetLimit::CanDestroyWithdrawal,
+            AssetLimit::CanDestroyFree,
+        ])
+    }
+}
+
 // ============================================================================
 // xassets/records types.
 // ============================================================================
@@ -275,6 +402,19 @@ pub enum ApplicationState {
     RootCancel,
 }
 
+impl Schema for ApplicationState {
+    fn schema() -> Value {
+        variants(&[
+            ApplicationState::Applying,
+            ApplicationState::Processing,
+            ApplicationState::NormalFinish,
+            ApplicationState::RootFinish,
+            ApplicationState::NormalCancel,
+            ApplicationState::RootCancel,
+        ])
+    }
+}
+
 impl Default for ApplicationState {
     fn default() -> Self {
         ApplicationState::Applying
@@ -301,6 +441,26 @@ where
     pub height: BlockNumber,
 }
 
+impl<AccountId, Balance, BlockNumber> Schema for Application<AccountId, Balance, BlockNumber>
+where
+    AccountId: Clone + Default + Codec + Schema,
+    Balance: Copy + Default + Codec + Schema,
+    BlockNumber: Copy + Default + Codec + Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            ("id", field(|v: &Self| &v.id)),
+            ("state", field(|v: &Self| &v.state)),
+            ("applicant", field(|v: &Self| &v.applicant)),
+            ("token", field(|v: &Self| &v.token)),
+            ("balance", field(|v: &Self| &v.balance)),
+            ("addr", field(|v: &Self| &v.addr)),
+            ("ext", field(|v: &Self| &v.ext)),
+            ("height", field(|v: &Self| &v.height)),
+        ])
+    }
+}
+
 impl<AccountId, Balance, BlockNumber> NodeT for Application<AccountId, Balance, BlockNumber>
 where
     AccountId: Clone + Default + Codec,
@@ -331,6 +491,26 @@ where
     pub last_total_vote_weight_update: BlockNumber,
 }
 
+impl<Balance, BlockNumber> Schema for IntentionProfs<Balance, BlockNumber>
+where
+    Balance: Copy + Default + Codec + Schema,
+    BlockNumber: Copy + Default + Codec + Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            ("total_nomination", field(|v: &Self| &v.total_nomination)),
+            (
+                "last_total_vote_weight",
+                field(|v: &Self| &v.last_total_vote_weight),
+            ),
+            (
+                "last_total_vote_weight_update",
+                field(|v: &Self| &v.last_total_vote_weight_update),
+            ),
+        ])
+    }
+}
+
 /// Intention mutable properties v1
 #[derive(PartialEq, Eq, Clone, Default, Debug)]
 #[derive(Encode, Decode)]
@@ -345,6 +525,26 @@ where
     pub last_total_vote_weight_update: BlockNumber,
 }
 
+impl<Balance, BlockNumber> Schema for IntentionProfsV1<Balance, BlockNumber>
+where
+    Balance: Copy + Default + Codec + Schema,
+    BlockNumber: Copy + Default + Codec + Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            ("total_nomination", field(|v: &Self| &v.total_nomination)),
+            (
+                "last_total_vote_weight",
+                field(|v: &Self| &v.last_total_vote_weight),
+            ),
+            (
+                "last_total_vote_weight_update",
+                field(|v: &Self| &v.last_total_vote_weight_update),
+            ),
+        ])
+    }
+}
+
 /// Nomination record of one of the nominator's nominations.
 #[derive(PartialEq, Eq, Clone, Default, Debug)]
 #[derive(Encode, Decode)]
@@ -360,6 +560,24 @@ where
     pub revocations: Vec<(BlockNumber, Balance)>,
 }
 
+impl<Balance, BlockNumber> Schema for NominationRecord<Balance, BlockNumber>
+where
+    Balance: Copy + Default + Codec + Schema,
+    BlockNumber: Copy + Default + Codec + Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            ("nomination", field(|v: &Self| &v.nomination)),
+            ("last_vote_weight", field(|v: &Self| &v.last_vote_weight)),
+            (
+                "last_vote_weight_update",
+                field(|v: &Self| &v.last_vote_weight_update),
+            ),
+            ("revocations", field(|v: &Self| &v.revocations)),
+        ])
+    }
+}
+
 /// Nomination record v1 of one of the nominator's nominations.
 #[derive(PartialEq, Eq, Clone, Default, Debug)]
 #[derive(Encode, Decode)]
@@ -375,6 +593,24 @@ where
     pub revocations: Vec<(BlockNumber, Balance)>,
 }
 
+impl<Balance, BlockNumber> Schema for NominationRecordV1<Balance, BlockNumber>
+where
+    Balance: Copy + Default + Codec + Schema,
+    BlockNumber: Copy + Default + Codec + Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            ("nomination", field(|v: &Self| &v.nomination)),
+            ("last_vote_weight", field(|v: &Self| &v.last_vote_weight)),
+            (
+                "last_vote_weight_update",
+                field(|v: &Self| &v.last_vote_weight_update),
+            ),
+            ("revocations", field(|v: &Self| &v.revocations)),
+        ])
+    }
+}
+
 // ============================================================================
 // xmining/tokens types.
 // ============================================================================
@@ -392,6 +628,24 @@ where
     pub last_total_deposit_weight_update: BlockNumber,
 }
 
+impl<BlockNumber> Schema for PseduIntentionVoteWeight<BlockNumber>
+where
+    BlockNumber: Copy + Default + Codec + Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            (
+                "last_total_deposit_weight",
+                field(|v: &Self| &v.last_total_deposit_weight),
+            ),
+            (
+                "last_total_deposit_weight_update",
+                field(|v: &Self| &v.last_total_deposit_weight_update),
+            ),
+        ])
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Default, Debug)]
 #[derive(Encode, Decode)]
 #[derive(Serialize, Deserialize)]
@@ -403,6 +657,24 @@ where
     pub last_total_deposit_weight_update: BlockNumber,
 }
 
+impl<BlockNumber> Schema for PseduIntentionVoteWeightV1<BlockNumber>
+where
+    BlockNumber: Copy + Default + Codec + Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            (
+                "last_total_deposit_weight",
+                field(|v: &Self| &v.last_total_deposit_weight),
+            ),
+            (
+                "last_total_deposit_weight_update",
+                field(|v: &Self| &v.last_total_deposit_weight_update),
+            ),
+        ])
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Default, Debug)]
 #[derive(Encode, Decode)]
 #[derive(Serialize, Deserialize)]
@@ -414,6 +686,24 @@ where
     pub last_deposit_weight_update: BlockNumber,
 }
 
+impl<BlockNumber> Schema for DepositVoteWeight<BlockNumber>
+where
+    BlockNumber: Copy + Default + Codec + Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            (
+                "last_deposit_weight",
+                field(|v: &Self| &v.last_deposit_weight),
+            ),
+            (
+                "last_deposit_weight_update",
+                field(|v: &Self| &v.last_deposit_weight_update),
+            ),
+        ])
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Default, Debug)]
 #[derive(Encode, Decode)]
 #[derive(Serialize, Deserialize)]
@@ -425,6 +715,24 @@ where
     pub last_deposit_weight_update: BlockNumber,
 }
 
+impl<BlockNumber> Schema for DepositVoteWeightV1<BlockNumber>
+where
+    BlockNumber: Copy + Default + Codec + Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            (
+                "last_deposit_weight",
+                field(|v: &Self| &v.last_deposit_weight),
+            ),
+            (
+                "last_deposit_weight_update",
+                field(|v: &Self| &v.last_deposit_weight_update),
+            ),
+        ])
+    }
+}
+
 // ============================================================================
 // xmultisig types.
 // ============================================================================
@@ -438,6 +746,12 @@ pub enum AddrType {
     Trustee,
 }
 
+impl Schema for AddrType {
+    fn schema() -> Value {
+        variants(&[AddrType::Normal, AddrType::Root, AddrType::Trustee])
+    }
+}
+
 impl Default for AddrType {
     fn default() -> Self {
         AddrType::Normal
@@ -452,6 +766,15 @@ pub enum MultiSigPermission {
     ConfirmAndPropose,
 }
 
+impl Schema for MultiSigPermission {
+    fn schema() -> Value {
+        variants(&[
+            MultiSigPermission::ConfirmOnly,
+            MultiSigPermission::ConfirmAndPropose,
+        ])
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Default, Debug)]
 #[derive(Encode, Decode)]
 #[derive(Serialize, Deserialize)]
@@ -464,6 +787,19 @@ where
     pub owner_list: Vec<(AccountId, MultiSigPermission)>,
 }
 
+impl<AccountId> Schema for AddrInfo<AccountId>
+where
+    AccountId: Clone + Default + Codec + Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            ("addr_type", field(|v: &Self| &v.addr_type)),
+            ("required_num", field(|v: &Self| &v.required_num)),
+            ("owner_list", field(|v: &Self| &v.owner_list)),
+        ])
+    }
+}
+
 // ============================================================================
 // xdex/spot types.
 // ============================================================================
@@ -480,6 +816,12 @@ pub type TradingPairIndex = u32;
 #[derive(Serialize, Deserialize)]
 pub struct CurrencyPair(pub Token, pub Token);
 
+impl Schema for CurrencyPair {
+    fn schema() -> Value {
+        tuple(vec![field(|v: &Self| &v.0), field(|v: &Self| &v.1)])
+    }
+}
+
 /// PCX/BTC = pip, a.k.a, percentage in point. Also called exchange rate.
 /// tick precision for BTC
 #[derive(PartialEq, Eq, Clone, Default, Debug)]
@@ -493,6 +835,18 @@ pub struct TradingPair {
     pub online: bool,
 }
 
+impl Schema for TradingPair {
+    fn schema() -> Value {
+        object(vec![
+            ("id", field(|v: &Self| &v.id)),
+            ("currency_pair", field(|v: &Self| &v.currency_pair)),
+            ("pip_precision", field(|v: &Self| &v.pip_precision)),
+            ("tick_precision", field(|v: &Self| &v.tick_precision)),
+            ("online", field(|v: &Self| &v.online)),
+        ])
+    }
+}
+
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
 #[derive(Encode, Decode)]
 #[derive(Serialize, Deserialize)]
@@ -501,6 +855,12 @@ pub enum OrderType {
     Market,
 }
 
+impl Schema for OrderType {
+    fn schema() -> Value {
+        variants(&[OrderType::Limit, OrderType::Market])
+    }
+}
+
 impl Default for OrderType {
     fn default() -> Self {
         OrderType::Limit
@@ -515,6 +875,12 @@ pub enum Side {
     Sell,
 }
 
+impl Schema for Side {
+    fn schema() -> Value {
+        variants(&[Side::Buy, Side::Sell])
+    }
+}
+
 impl Default for Side {
     fn default() -> Self {
         Side::Buy
@@ -532,6 +898,18 @@ pub enum OrderStatus {
     Canceled,
 }
 
+impl Schema for OrderStatus {
+    fn schema() -> Value {
+        variants(&[
+            OrderStatus::ZeroFill,
+            OrderStatus::ParitialFill,
+            OrderStatus::Filled,
+            OrderStatus::ParitialFillAndCanceled,
+            OrderStatus::Canceled,
+        ])
+    }
+}
+
 impl Default for OrderStatus {
     fn default() -> Self {
         OrderStatus::ZeroFill
@@ -558,6 +936,29 @@ pub struct OrderProperty<
     BlockNumber,
 );
 
+impl<PairIndex, AccountId, Balance, Price, BlockNumber> Schema
+    for OrderProperty<PairIndex, AccountId, Balance, Price, BlockNumber>
+where
+    PairIndex: Clone + Default + Codec + Schema,
+    AccountId: Clone + Default + Codec + Schema,
+    Balance: Copy + Default + Codec + Schema,
+    Price: Copy + Default + Codec + Schema,
+    BlockNumber: Copy + Default + Codec + Schema,
+{
+    fn schema() -> Value {
+        tuple(vec![
+            field(|v: &Self| &v.0),
+            field(|v: &Self| &v.1),
+            field(|v: &Self| &v.2),
+            field(|v: &Self| &v.3),
+            field(|v: &Self| &v.4),
+            field(|v: &Self| &v.5),
+            field(|v: &Self| &v.6),
+            field(|v: &Self| &v.7),
+        ])
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Default, Debug)]
 #[derive(Encode, Decode)]
 #[derive(Serialize, Deserialize)]
@@ -578,6 +979,27 @@ where
     pub last_update_at: BlockNumber,
 }
 
+impl<PairIndex, AccountId, Balance, Price, BlockNumber> Schema
+    for Order<PairIndex, AccountId, Balance, Price, BlockNumber>
+where
+    PairIndex: Clone + Default + Codec + Schema,
+    AccountId: Clone + Default + Codec + Schema,
+    Balance: Copy + Default + Codec + Schema,
+    Price: Copy + Default + Codec + Schema,
+    BlockNumber: Copy + Default + Codec + Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            ("props", field(|v: &Self| &v.props)),
+            ("status", field(|v: &Self| &v.status)),
+            ("remaining", field(|v: &Self| &v.remaining)),
+            ("executed_indices", field(|v: &Self| &v.executed_indices)),
+            ("already_filled", field(|v: &Self| &v.already_filled)),
+            ("last_update_at", field(|v: &Self| &v.last_update_at)),
+        ])
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Default, Debug)]
 #[derive(Encode, Decode)]
 #[derive(Serialize, Deserialize)]
@@ -589,6 +1011,18 @@ where
     pub lowest_offer: Price,
 }
 
+impl<Price> Schema for Handicap<Price>
+where
+    Price: Copy + Default + Codec + Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            ("highest_bid", field(|v: &Self| &v.highest_bid)),
+            ("lowest_offer", field(|v: &Self| &v.lowest_offer)),
+        ])
+    }
+}
+
 // ============================================================================
 // xbridge/bitcoin types.
 // ============================================================================
@@ -597,12 +1031,23 @@ where
 #[derive(Encode, Decode)]
 #[derive(Serialize, Deserialize)]
 pub struct BlockHeaderInfo {
-    pub header: btc::BlockHeader,
+    pub header: bitcoin::BlockHeader,
     pub height: u32,
     pub confirmed: bool,
     pub txid_list: Vec<H256>,
 }
 
+impl Schema for BlockHeaderInfo {
+    fn schema() -> Value {
+        object(vec![
+            ("header", field(|v: &Self| &v.header)),
+            ("height", field(|v: &Self| &v.height)),
+            ("confirmed", field(|v: &Self| &v.confirmed)),
+            ("txid_list", field(|v: &Self| &v.txid_list)),
+        ])
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 #[derive(Encode, Decode)]
 #[derive(Serialize, Deserialize)]
@@ -616,6 +1061,20 @@ pub enum TxType {
     Irrelevance,
 }
 
+impl Schema for TxType {
+    fn schema() -> Value {
+        variants(&[
+            TxType::Withdraw,
+            TxType::Deposit,
+            TxType::HotAndCold,
+            TxType::TrusteeTransition,
+            TxType::Lock,
+            TxType::Unlock,
+            TxType::Irrelevance,
+        ])
+    }
+}
+
 impl Default for TxType {
     fn default() -> Self {
         TxType::Deposit
@@ -626,12 +1085,23 @@ impl Default for TxType {
 #[derive(Encode, Decode)]
 #[derive(Serialize, Deserialize)]
 pub struct TxInfo {
-    pub raw_tx: btc::Transaction,
+    pub raw_tx: bitcoin::Transaction,
     pub tx_type: TxType,
     pub height: u32,
     pub done: bool,
 }
 
+impl Schema for TxInfo {
+    fn schema() -> Value {
+        object(vec![
+            ("raw_tx", field(|v: &Self| &v.raw_tx)),
+            ("tx_type", field(|v: &Self| &v.tx_type)),
+            ("height", field(|v: &Self| &v.height)),
+            ("done", field(|v: &Self| &v.done)),
+        ])
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
 #[derive(Encode, Decode)]
 #[derive(Serialize, Deserialize)]
@@ -640,6 +1110,15 @@ pub struct DepositCache {
     pub balance: u64,
 }
 
+impl Schema for DepositCache {
+    fn schema() -> Value {
+        object(vec![
+            ("txid", field(|v: &Self| &v.txid)),
+            ("balance", field(|v: &Self| &v.balance)),
+        ])
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
 #[derive(Encode, Decode)]
 #[derive(Serialize, Deserialize)]
@@ -648,6 +1127,12 @@ pub enum VoteResult {
     Finish,
 }
 
+impl Schema for VoteResult {
+    fn schema() -> Value {
+        variants(&[VoteResult::Unfinish, VoteResult::Finish])
+    }
+}
+
 impl Default for VoteResult {
     fn default() -> Self {
         VoteResult::Unfinish
@@ -663,10 +1148,27 @@ where
 {
     pub sig_state: VoteResult,
     pub withdrawal_id_list: Vec<u32>,
-    pub tx: btc::Transaction,
+    pub tx: bitcoin::Transaction,
     pub trustee_list: Vec<(AccountId, bool)>,
 }
 
+impl<AccountId> Schema for WithdrawalProposal<AccountId>
+where
+    AccountId: Clone + Default + Codec + Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            ("sig_state", field(|v: &Self| &v.sig_state)),
+            (
+                "withdrawal_id_list",
+                field(|v: &Self| &v.withdrawal_id_list),
+            ),
+            ("tx", field(|v: &Self| &v.tx)),
+            ("trustee_list", field(|v: &Self| &v.trustee_list)),
+        ])
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Copy, Default, Debug)]
 #[derive(Encode, Decode)]
 #[derive(Serialize, Deserialize)]
@@ -684,14 +1186,71 @@ pub struct Params {
     max_timespan: u32,
 }
 
+impl Schema for Params {
+    fn schema() -> Value {
+        object(vec![
+            ("max_bits", field(|v: &Self| &v.max_bits)),
+            ("block_max_future", field(|v: &Self| &v.block_max_future)),
+            (
+                "target_timespan_seconds",
+                field(|v: &Self| &v.target_timespan_seconds),
+            ),
+            (
+                "target_spacing_seconds",
+                field(|v: &Self| &v.target_spacing_seconds),
+            ),
+            (
+                "retargeting_factor",
+                field(|v: &Self| &v.retargeting_factor),
+            ),
+            (
+                "retargeting_interval",
+                field(|v: &Self| &v.retargeting_interval),
+            ),
+            ("min_timespan", field(|v: &Self| &v.min_timespan)),
+            ("max_timespan", field(|v: &Self| &v.max_timespan)),
+        ])
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Default, Debug)]
 #[derive(Encode, Decode)]
-#[derive(Serialize, Deserialize)]
+#[derive(Deserialize)]
 pub struct TrusteeAddrInfo {
-    pub addr: btc::Address,
+    pub addr: bitcoin::Address,
     pub redeem_script: Bytes,
 }
 
+/// Serialized with the decoded multisig redeem script,
+/// and whether the redeem script hashes to the address.
+impl Serialize for TrusteeAddrInfo {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let addr = self.addr.0.to_string();
+        let address_verified = bitcoin::is_p2sh_address_of(&addr, &self.redeem_script.0);
+        serde_json::json!({
+            "addr": addr,
+            "redeem_script": self.redeem_script,
+            "multisig": bitcoin::multisig_to_json(&self.redeem_script.0),
+            "address_verified": address_verified,
+        })
+        .serialize(serializer)
+    }
+}
+
+impl Schema for TrusteeAddrInfo {
+    fn schema() -> Value {
+        object(vec![
+            ("addr", field(|v: &Self| &v.addr)),
+            ("redeem_script", field(|v: &Self| &v.redeem_script)),
+            ("multisig", bitcoin::multisig_schema()),
+            ("address_verified", json!({"type": "boolean"})),
+        ])
+    }
+}
+
 impl IntoVecu8 for TrusteeAddrInfo {
     fn into_vecu8(self) -> Vec<u8> {
         parity_codec::Encode::encode(&self)
@@ -706,7 +1265,79 @@ impl IntoVecu8 for TrusteeAddrInfo {
 // xbridge/sdot types.
 // ============================================================================
 
-pub type EthereumAddress = primitive_types::H160;
+/// Ethereum address, serialized in the EIP-55 mixed-case checksum form.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Default, Debug)]
+#[derive(Encode, Decode)]
+pub struct EthereumAddress(pub primitive_types::H160);
+
+impl EthereumAddress {
+    /// Parse the `0x` prefixed hex address, the checksum is verified if it's in mixed case.
+    pub fn from_checksum_str(s: &str) -> Option<Self> {
+        let hex_str = s.strip_prefix("0x").unwrap_or(s);
+        let bytes = hex::decode(hex_str).ok()?;
+        if bytes.len() != 20 {
+            return None;
+        }
+        let address = EthereumAddress(primitive_types::H160::from_slice(&bytes));
+        let is_mixed_case = hex_str.chars().any(|c| c.is_ascii_lowercase())
+            && hex_str.chars().any(|c| c.is_ascii_uppercase());
+        if is_mixed_case && address.to_checksum_str()[2..] != *hex_str {
+            return None;
+        }
+        Some(address)
+    }
+
+    /// Render the address in the EIP-55 checksum form.
+    pub fn to_checksum_str(&self) -> String {
+        let hex_str = hex::encode(self.0);
+        let hash = tiny_keccak::keccak256(hex_str.as_bytes());
+        let checksum = hex_str
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let nibble = (hash[i / 2] >> (if i % 2 == 0 { 4 } else { 0 })) & 0x0f;
+                if nibble >= 8 {
+                    c.to_ascii_uppercase()
+                } else {
+                    c
+                }
+            })
+            .collect::<String>();
+        format!("0x{}", checksum)
+    }
+}
+
+impl core::fmt::Display for EthereumAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "{}", self.to_checksum_str())
+    }
+}
+
+impl Serialize for EthereumAddress {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.to_checksum_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for EthereumAddress {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        EthereumAddress::from_checksum_str(&s)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid ethereum address: {}", s)))
+    }
+}
+
+impl Schema for EthereumAddress {
+    fn schema() -> Value {
+        json!({"type": "string", "pattern": "^0x[0-9a-fA-F]{40}$", "description": "EIP-55 checksum address"})
+    }
+}
 
 // ============================================================================
 // xbridge/features types.
@@ -752,6 +1383,15 @@ pub struct TrusteeInfoConfig {
     pub max_trustee_count: u32,
 }
 
+impl Schema for TrusteeInfoConfig {
+    fn schema() -> Value {
+        object(vec![
+            ("min_trustee_count", field(|v: &Self| &v.min_trustee_count)),
+            ("max_trustee_count", field(|v: &Self| &v.max_trustee_count)),
+        ])
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Default, Debug)]
 #[derive(Encode, Decode)]
 #[derive(Serialize, Deserialize)]
@@ -764,6 +1404,19 @@ where
     pub cold_entity: TrusteeEntity,
 }
 
+impl<TrusteeEntity> Schema for TrusteeIntentionProps<TrusteeEntity>
+where
+    TrusteeEntity: IntoVecu8 + Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            ("about", field(|v: &Self| &v.about)),
+            ("hot_entity", field(|v: &Self| &v.hot_entity)),
+            ("cold_entity", field(|v: &Self| &v.cold_entity)),
+        ])
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Default, Debug)]
 #[derive(Encode, Decode)]
 #[derive(Serialize, Deserialize)]
@@ -777,15 +1430,29 @@ where
     pub cold_address: TrusteeAddress,
 }
 
-pub type BitcoinTrusteeType = btc::Public;
+impl<AccountId, TrusteeAddress> Schema for TrusteeSessionInfo<AccountId, TrusteeAddress>
+where
+    AccountId: Clone + Default + Codec + Schema,
+    TrusteeAddress: IntoVecu8 + Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            ("trustee_list", field(|v: &Self| &v.trustee_list)),
+            ("hot_address", field(|v: &Self| &v.hot_address)),
+            ("cold_address", field(|v: &Self| &v.cold_address)),
+        ])
+    }
+}
+
+pub type BitcoinTrusteeType = bitcoin::Public;
 
 impl IntoVecu8 for BitcoinTrusteeType {
     fn into_vecu8(self) -> Vec<u8> {
-        (&self).to_vec()
+        (&self.0).to_vec()
     }
 
     fn from_vecu8(src: &[u8]) -> Option<Self> {
-        Self::from_slice(src).ok()
+        btc::Public::from_slice(src).ok().map(bitcoin::Public)
     }
 }
 
@@ -0,0 +1,396 @@
+//! The self-describing catalog of runtime storages.
+//!
+//! The JSON Schemas of key and value come from the `Schema` implementations of the decoded types,
+//! and the key/value types of every storage are taken from the fields of its `RuntimeStorage` variant,
+//! so that a new variant can't be added without its catalog entry.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use strum::{EnumProperty, IntoEnumIterator};
+
+use super::RuntimeStorage;
+use crate::types::schema::{self, nullable, object, uint, Schema};
+
+/// The catalog entry of runtime storage.
+#[derive(PartialEq, Clone, Debug, Serialize)]
+pub struct CatalogEntry {
+    pub prefix: &'static str,
+    /// The storage kind, i.e. `value`, `map` or `linked_map`.
+    #[serde(rename = "type")]
+    pub kind: &'static str,
+    /// The type name of key, `null` for the storage value.
+    pub key_type: Option<String>,
+    pub value_type: String,
+    /// The JSON Schema of the decoded key, `null` for the storage value.
+    pub key_schema: Option<Value>,
+    /// The JSON Schema of the decoded value, which allows `null` since the value is `null`
+    /// when the entry is removed.
+    pub value_schema: Value,
+}
+
+/// List every runtime storage with its storage kind, key/value type names and JSON Schemas.
+pub fn catalog() -> Vec<CatalogEntry> {
+    RuntimeStorage::iter()
+        .map(|storage| {
+            let (key, value) = parts_of(&storage);
+            CatalogEntry {
+                prefix: (&storage).into(),
+                kind: storage.get_str("Type").unwrap_or_default(),
+                key_type: key.as_ref().map(|key| key.type_name.clone()),
+                value_type: value.type_name,
+                key_schema: key.map(|key| key.schema),
+                value_schema: nullable(value.schema),
+            }
+        })
+        .collect()
+}
+
+/// The key or value of runtime storage.
+struct Part {
+    type_name: String,
+    schema: Value,
+    /// The serialized default value, which is checked against the schema by tests.
+    #[cfg(test)]
+    sample: Value,
+}
+
+impl Part {
+    fn of<T: Schema + Serialize>(_value: &T) -> Self {
+        Self {
+            type_name: short_type_name(std::any::type_name::<T>()),
+            schema: T::schema(),
+            #[cfg(test)]
+            sample: serde_json::to_value(_value).expect("Serialize the default value"),
+        }
+    }
+
+    /// The part that isn't serialized from its type, e.g. the runtime code.
+    fn new(type_name: &str, schema: Value) -> Self {
+        Self {
+            type_name: type_name.to_string(),
+            schema,
+            #[cfg(test)]
+            sample: Value::Null,
+        }
+    }
+}
+
+fn value<V: Schema + Serialize>(v: &V) -> (Option<Part>, Part) {
+    (None, Part::of(v))
+}
+
+fn map<K: Schema + Serialize, V: Schema + Serialize>(k: &K, v: &V) -> (Option<Part>, Part) {
+    (Some(Part::of(k)), Part::of(v))
+}
+
+/// The key (if any) and value of the runtime storage, the value of `linked_map` excludes its linkage
+/// (see `split_linkage`).
+#[rustfmt::skip]
+fn parts_of(storage: &RuntimeStorage) -> (Option<Part>, Part) {
+    use RuntimeStorage::*;
+    match storage {
+        WellKnownCode(_) => (None, Part::new("Vec<u8>", code_schema())),
+        WellKnownHeapPages(v) => value(v),
+        WellKnownAuthLen(v) => value(v),
+        WellKnownAuth(k, v) => map(k, v),
+        WellKnownChangesTrie(v) => value(v),
+        SystemNumber(v) => value(v),
+        SystemParentHash(v) => value(v),
+        SystemEvents(_) => (None, Part::new("Vec<EventRecord>", events_schema())),
+        SystemAccountNonce(k, v) => map(k, v),
+        SystemBlockHash(k, v) => map(k, v),
+        IndicesNextEnumSet(v) => value(v),
+        IndicesEnumSet(k, v) => map(k, v),
+        TimestampNow(v) => value(v),
+        TimestampBlockPeriod(v) => value(v),
+        TimestampMinimumPeriod(v) => value(v),
+        TimestampWindowSize(v) => value(v),
+        TimestampReportLatency(v) => value(v),
+        SessionValidators(v) => value(v),
+        SessionSessionLength(v) => value(v),
+        SessionCurrentIndex(v) => value(v),
+        SessionCurrentStart(v) => value(v),
+        SessionSessionTotalMissedBlocksCount(v) => value(v),
+        SessionForcingNewSession(v) => value(v),
+        XSystemBlockProducer(v) => value(v),
+        XSystemNetworkProps(v) => value(v),
+        XAccountsIntentionOf(k, v) => map(k, v),
+        XAccountsIntentionNameOf(k, v) => map(k, v),
+        XAccountsIntentionPropertiesOf(k, v) => map(k, v),
+        XAccountsTeamAddress(v) => value(v),
+        XAccountsCouncilAddress(v) => value(v),
+        XAccountsBlockedAccounts(v) => value(v),
+        XFeeManagerSwitcher(v) => value(v),
+        XFeeManagerMethodCallWeight(v) => value(v),
+        XFeeManagerProducerFeeProportion(v) => value(v),
+        XFeeManagerTransactionBaseFee(v) => value(v),
+        XFeeManagerTransactionByteFee(v) => value(v),
+        XAssetsAssetList(k, v) => map(k, v),
+        XAssetsAssetInfo(k, v) => map(k, v),
+        XAssetsAssetLimitProps(k, v) => map(k, v),
+        XAssetsAssetBalance(k, v) => map(k, v),
+        XAssetsTotalAssetBalance(k, v) => map(k, v),
+        XAssetsMemoLen(v) => value(v),
+        XAssetsRecordsApplicationMHeader(k, v) => map(k, v),
+        XAssetsRecordsApplicationMTail(k, v) => map(k, v),
+        XAssetsRecordsApplicationMap(k, v) => map(k, v),
+        XAssetsRecordsSerialNumber(v) => value(v),
+        XFisherReported(k, v) => map(k, v),
+        XFisherFishermen(v) => value(v),
+        XStakingInitialReward(v) => value(v),
+        XStakingValidatorCount(v) => value(v),
+        XStakingMinimumValidatorCount(v) => value(v),
+        XStakingSessionsPerEra(v) => value(v),
+        XStakingBondingDuration(v) => value(v),
+        XStakingIntentionBondingDuration(v) => value(v),
+        XStakingMaximumIntentionCount(v) => value(v),
+        XStakingSessionsPerEpoch(v) => value(v),
+        XStakingCurrentEra(v) => value(v),
+        XStakingDistributionRatio(v) => value(v),
+        XStakingNextSessionsPerEra(v) => value(v),
+        XStakingLastEraLengthChange(v) => value(v),
+        XStakingForcingNewEra(v) => value(v),
+        XStakingStakeWeight(k, v) => map(k, v),
+        XStakingIntentions(k, (v, _)) => map(k, v),
+        XStakingIntentionsHead(v) => value(v),
+        XStakingIntentionsV1(k, (v, _)) => map(k, v),
+        XStakingIntentionsV1Head(v) => value(v),
+        XStakingNominationRecords(k, v) => map(k, v),
+        XStakingNominationRecordsV1(k, v) => map(k, v),
+        XStakingUpperBoundFactor(v) => value(v),
+        XStakingEvilValidatorsPerSession(v) => value(v),
+        XStakingLastRenominationOf(k, v) => map(k, v),
+        XStakingMaxUnbondEntriesPerIntention(v) => value(v),
+        XStakingMinimumPenalty(v) => value(v),
+        XStakingOfflineValidatorsPerSession(v) => value(v),
+        XStakingMissedOfPerSession(k, v) => map(k, v),
+        XStakingMissedBlockSeverity(v) => value(v),
+        XTokensTokenDiscount(k, v) => map(k, v),
+        XTokensPseduIntentions(v) => value(v),
+        XTokensClaimRestrictionOf(k, v) => map(k, v),
+        XTokensLastClaimOf(k, v) => map(k, v),
+        XTokensPseduIntentionProfiles(k, v) => map(k, v),
+        XTokensPseduIntentionProfilesV1(k, v) => map(k, v),
+        XTokensDepositRecords(k, v) => map(k, v),
+        XTokensDepositRecordsV1(k, v) => map(k, v),
+        XTokensDepositReward(v) => value(v),
+        XMultiSigRootAddrList(v) => value(v),
+        XMultiSigMultiSigAddrInfo(k, v) => map(k, v),
+        XMultiSigPendingListFor(k, v) => map(k, v),
+        XMultiSigMultiSigListItemFor(k, v) => map(k, v),
+        XMultiSigMultiSigListLenFor(k, v) => map(k, v),
+        XSpotTradingPairCount(v) => value(v),
+        XSpotTradingPairOf(k, v) => map(k, v),
+        XSpotTradingPairInfoOf(k, v) => map(k, v),
+        XSpotTradeHistoryIndexOf(k, v) => map(k, v),
+        XSpotOrderCountOf(k, v) => map(k, v),
+        XSpotOrderInfoOf(k, v) => map(k, v),
+        XSpotQuotationsOf(k, v) => map(k, v),
+        XSpotHandicapOf(k, v) => map(k, v),
+        XSpotPriceVolatility(v) => value(v),
+        XBridgeCommonCrossChainBinding(k, v) => map(k, v),
+        XBridgeOfBTCBestIndex(v) => value(v),
+        XBridgeOfBTCBlockHashFor(k, v) => map(k, v),
+        XBridgeOfBTCBlockHeaderFor(k, v) => map(k, v),
+        XBridgeOfBTCTxFor(k, v) => map(k, v),
+        XBridgeOfBTCTxMarkFor(k, v) => map(k, v),
+        XBridgeOfBTCInputAddrFor(k, v) => map(k, v),
+        XBridgeOfBTCPendingDepositMap(k, v) => map(k, v),
+        XBridgeOfBTCCurrentWithdrawalProposal(v) => value(v),
+        XBridgeOfBTCGenesisInfo(v) => value(v),
+        XBridgeOfBTCParamsInfo(v) => value(v),
+        XBridgeOfBTCNetworkId(v) => value(v),
+        XBridgeOfBTCReservedBlock(v) => value(v),
+        XBridgeOfBTCConfirmationNumber(v) => value(v),
+        XBridgeOfBTCBtcWithdrawalFee(v) => value(v),
+        XBridgeOfBTCBtcMinDeposit(v) => value(v),
+        XBridgeOfBTCMaxWithdrawalCount(v) => value(v),
+        XBridgeOfBTCLockupLockedUpBTC(k, v) => map(k, v),
+        XBridgeOfBTCLockupAddressLockedCoin(k, v) => map(k, v),
+        XBridgeOfBTCLockupLockedCoinLimit(v) => value(v),
+        XBridgeOfSDOTClaims(k, v) => map(k, v),
+        XBridgeOfSDOTTotal(v) => value(v),
+        XBridgeFeaturesTrusteeMultiSigAddr(k, v) => map(k, v),
+        XBridgeFeaturesTrusteeInfoConfigOf(k, v) => map(k, v),
+        XBridgeFeaturesTrusteeSessionInfoLen(k, v) => map(k, v),
+        XBridgeFeaturesBitcoinTrusteeSessionInfoOf(k, v) => map(k, v),
+        XBridgeFeaturesBitcoinTrusteeIntentionPropertiesOf(k, v) => map(k, v),
+        XBridgeFeaturesBitcoinCrossChainBinding(k, v) => map(k, v),
+        XBridgeFeaturesBitcoinCrossChainOf(k, v) => map(k, v),
+        XBridgeFeaturesEthereumCrossChainBinding(k, v) => map(k, v),
+        XBridgeFeaturesEthereumCrossChainOf(k, v) => map(k, v),
+    }
+}
+
+/// Strip the module paths of the type name, e.g. `alloc::vec::Vec<u32>` => `Vec<u32>`.
+fn short_type_name(name: &str) -> String {
+    let mut short = String::new();
+    let mut path = String::new();
+    for c in name.chars().chain(std::iter::once('\0')) {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            path.push(c);
+            continue;
+        }
+        short.push_str(path.rsplit("::").next().unwrap_or_default());
+        path.clear();
+        if c != '\0' {
+            short.push(c);
+        }
+    }
+    short
+}
+
+/// The runtime code is reported with the hash and size of the blob (see `code_to_json`).
+fn code_schema() -> Value {
+    object(vec![("hash", schema::hex(Some(32))), ("size", uint())])
+}
+
+/// The events are decoded by the event registry (see `event` module).
+fn events_schema() -> Value {
+    json!({
+        "type": "array",
+        "items": {
+            "type": "object",
+            "properties": {
+                "phase": {},
+                "module": {"type": "string"},
+                "event": {"type": "string"},
+                "args": {"type": "array"},
+                "error": {"type": "string"},
+                "remaining": schema::hex(None),
+            },
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Check the value against the subset of JSON Schema that is generated by the `Schema`.
+    fn validate(schema: &Value, value: &Value) -> bool {
+        if let Some(schemas) = schema["anyOf"].as_array() {
+            return schemas.iter().any(|schema| validate(schema, value));
+        }
+        if let Some(names) = schema["enum"].as_array() {
+            return names.contains(value);
+        }
+        let matches_type = |ty: &Value| match ty.as_str() {
+            Some("null") => value.is_null(),
+            Some("boolean") => value.is_boolean(),
+            Some("integer") => value.is_u64() || value.is_i64(),
+            Some("string") => value.is_string(),
+            Some("array") => value.is_array(),
+            Some("object") => value.is_object(),
+            _ => true,
+        };
+        if !matches_type(&schema["type"]) {
+            return false;
+        }
+        if let Some(items) = value.as_array() {
+            if let Some(prefix_items) = schema["prefixItems"].as_array() {
+                return items.len() == prefix_items.len()
+                    && prefix_items
+                        .iter()
+                        .zip(items)
+                        .all(|(schema, item)| validate(schema, item));
+            }
+            if !schema["items"].is_null() {
+                return items.iter().all(|item| validate(&schema["items"], item));
+            }
+        }
+        if let Some(object) = value.as_object() {
+            let required = schema["required"].as_array().cloned().unwrap_or_default();
+            if !required
+                .iter()
+                .all(|name| object.contains_key(name.as_str().unwrap()))
+            {
+                return false;
+            }
+            let names = &schema["propertyNames"];
+            if !names.is_null() && !object.keys().all(|name| validate(names, &json!(name))) {
+                return false;
+            }
+            return object.iter().all(|(name, value)| {
+                match (&schema["properties"][name], &schema["additionalProperties"]) {
+                    (Value::Null, Value::Bool(false)) => false,
+                    (Value::Null, Value::Null) => true,
+                    (Value::Null, additional) => validate(additional, value),
+                    (property, _) => validate(property, value),
+                }
+            });
+        }
+        true
+    }
+
+    #[test]
+    fn test_catalog_matches_serialization() {
+        for storage in RuntimeStorage::iter() {
+            let (key, value) = parts_of(&storage);
+            let prefix: &'static str = (&storage).into();
+            if let Some(key) = key {
+                assert!(validate(&key.schema, &key.sample), "key of {}", prefix);
+            }
+            assert!(
+                validate(&value.schema, &value.sample),
+                "value of {}",
+                prefix
+            );
+        }
+    }
+
+    #[test]
+    fn test_catalog() {
+        let catalog = catalog();
+        assert_eq!(catalog.len(), RuntimeStorage::prefixes().len());
+        // the removed value is `null`
+        for entry in &catalog {
+            assert!(
+                validate(&entry.value_schema, &Value::Null),
+                "{}",
+                entry.prefix
+            );
+        }
+
+        let balance = catalog
+            .iter()
+            .find(|entry| entry.prefix == "XAssets AssetBalance")
+            .unwrap();
+        assert_eq!(balance.kind, "map");
+        assert_eq!(balance.key_type.as_deref(), Some("(Public, String)"));
+        assert_eq!(balance.value_type, "BTreeMap<AssetType, u64>");
+        let key = balance.key_schema.as_ref().unwrap();
+        assert_eq!(key["prefixItems"][0], schema::hex(Some(32)));
+        assert_eq!(key["prefixItems"][1], json!({"type": "string"}));
+        let value = &balance.value_schema["anyOf"][0];
+        assert_eq!(value["additionalProperties"], uint());
+        assert_eq!(value["propertyNames"]["enum"][0], "Free");
+        let balances = json!({"Free": 1, "ReservedStaking": 2});
+        assert!(validate(&balance.value_schema, &balances));
+        assert!(!validate(&balance.value_schema, &json!({"Unknown": 1})));
+
+        let intentions = catalog
+            .iter()
+            .find(|entry| entry.prefix == "XStaking Intentions")
+            .unwrap();
+        assert_eq!(intentions.kind, "linked_map");
+        assert_eq!(intentions.value_type, "IntentionProfs<u64, u64>");
+
+        let now = catalog
+            .iter()
+            .find(|entry| entry.prefix == "Timestamp Now")
+            .unwrap();
+        assert_eq!(now.key_type, None);
+        assert_eq!(now.value_schema, nullable(uint()));
+    }
+
+    #[test]
+    fn test_short_type_name() {
+        assert_eq!(short_type_name("alloc::vec::Vec<u32>"), "Vec<u32>");
+        assert_eq!(
+            short_type_name("(a::b::Public, alloc::string::String)"),
+            "(Public, String)"
+        );
+        assert_eq!(short_type_name("()"), "()");
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use parking_lot::{Condvar, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::Block;
+
+/// The queue of parsed blocks (key - block height, value - block) that are waiting to be pushed.
+pub type BlockQueue = Arc<Queue>;
+
+/// The blocks with the notification of changes, so that the push threads can wait for
+/// the new blocks instead of polling the queue.
+#[derive(Default)]
+pub struct Queue {
+    blocks: RwLock<BTreeMap<u64, Block>>,
+    /// The count of notifications, which is used to detect the notification
+    /// between checking the queue and waiting.
+    generation: Mutex<u64>,
+    changed: Condvar,
+}
+
+impl Queue {
+    pub fn read(&self) -> RwLockReadGuard<BTreeMap<u64, Block>> {
+        self.blocks.read()
+    }
+
+    pub fn write(&self) -> RwLockWriteGuard<BTreeMap<u64, Block>> {
+        self.blocks.write()
+    }
+
+    /// Wake up all waiters, e.g. a block has been inserted into the queue,
+    /// or the state of subscription has been changed.
+    pub fn notify(&self) {
+        *self.generation.lock() += 1;
+        self.changed.notify_all();
+    }
+
+    /// The current generation of notifications, which should be taken before checking the queue.
+    pub fn generation(&self) -> u64 {
+        *self.generation.lock()
+    }
+
+    /// Block until there is any notification after the generation was taken.
+    pub fn wait(&self, generation: u64) {
+        let mut current = self.generation.lock();
+        while *current == generation {
+            self.changed.wait(&mut current);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_queue_notify() {
+        let queue = BlockQueue::default();
+        let generation = queue.generation();
+        let waiter = {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                queue.wait(generation);
+                queue.read().len()
+            })
+        };
+        queue.write().insert(1, Block::new(vec![]));
+        queue.notify();
+        assert_eq!(waiter.join().unwrap(), 1);
+        // the notification has been taken before waiting
+        queue.wait(generation);
+    }
+}
@@ -0,0 +1,175 @@
+//! The account-centric subscription.
+//!
+//! The registrant gives a set of accounts instead of listing the prefixes one by one,
+//! every entry (of the subscribed prefixes) that carries any of the accounts in its key or value
+//! is pushed with the matching accounts (`"accounts":[..]`).
+//!
+//! The `AccountId`s of entry are located by the JSON pointers of its prefix, so that the other
+//! public keys and hashes (e.g. the session key, the linkage of `linked_map`) aren't matched.
+
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::parse::{account_event_prefixes, normalize_address, RuntimeStorage};
+
+lazy_static::lazy_static! {
+    /// The prefixes of the storages and events that carry `AccountId`,
+    /// with the JSON pointers of the `AccountId`s (`*` matches every element of array).
+    static ref ACCOUNT_PREFIXES: HashMap<String, Vec<String>> = {
+        let storages = RuntimeStorage::account_prefixes()
+            .into_iter()
+            .map(|(prefix, pointers)| {
+                let pointers = pointers.into_iter().map(ToString::to_string).collect();
+                (prefix.to_string(), pointers)
+            });
+        storages.chain(account_event_prefixes()).collect()
+    };
+}
+
+/// The set of subscribed accounts, which are given as SS58 or hex of public key.
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+#[derive(Serialize, Deserialize)]
+#[serde(try_from = "Vec<String>", into = "Vec<String>")]
+pub struct AccountSet {
+    accounts: Vec<String>,
+    /// The hex of public key => the account given by registrant.
+    index: HashMap<String, String>,
+}
+
+impl TryFrom<Vec<String>> for AccountSet {
+    type Error = String;
+
+    fn try_from(accounts: Vec<String>) -> Result<Self, Self::Error> {
+        let index = accounts
+            .iter()
+            .map(|account| match normalize_address(account) {
+                Some(public) if public.len() == 66 => Ok((public, account.clone())),
+                _ if is_public_hex(account) => Ok((account.to_lowercase(), account.clone())),
+                _ => Err(format!("Invalid account: {:?}", account)),
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { accounts, index })
+    }
+}
+
+impl From<AccountSet> for Vec<String> {
+    fn from(set: AccountSet) -> Self {
+        set.accounts
+    }
+}
+
+impl AccountSet {
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// The count of accounts of the set.
+    pub fn account_count(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Get the subscribed accounts (in the form given by registrant) that the entry carries,
+    /// empty if the entry doesn't carry `AccountId` at all.
+    pub fn touched(&self, prefix: &str, entry: &Value) -> Vec<String> {
+        let pointers = match ACCOUNT_PREFIXES.get(prefix) {
+            Some(pointers) => pointers,
+            None => return vec![],
+        };
+        let mut found = HashSet::new();
+        for pointer in pointers {
+            let segments = pointer.split('/').skip(1).collect::<Vec<_>>();
+            self.collect(entry, &segments, &mut found);
+        }
+        let mut touched = found.into_iter().cloned().collect::<Vec<_>>();
+        touched.sort();
+        touched
+    }
+
+    /// Collect the account at the path of segments, `*` matches every element of array.
+    fn collect<'a>(&'a self, value: &Value, segments: &[&str], found: &mut HashSet<&'a String>) {
+        match (segments.split_first(), value) {
+            (None, Value::String(s)) if is_public_hex(s) => {
+                if let Some(account) = self.index.get(&s.to_lowercase()) {
+                    found.insert(account);
+                }
+            }
+            (Some((&"*", rest)), Value::Array(array)) => {
+                array.iter().for_each(|v| self.collect(v, rest, found))
+            }
+            (Some((segment, rest)), Value::Array(array)) => {
+                if let Some(v) = segment.parse::<usize>().ok().and_then(|i| array.get(i)) {
+                    self.collect(v, rest, found);
+                }
+            }
+            (Some((segment, rest)), Value::Object(object)) => {
+                if let Some(v) = object.get(*segment) {
+                    self.collect(v, rest, found);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Check whether the string is the hex of 32 bytes public key.
+fn is_public_hex(s: &str) -> bool {
+    s.len() == 66 && s.starts_with("0x") && s[2..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_account_set_touched() {
+        let ss58 = "5RxWcMTkfpZiZ8TQirESQhiM7hDT4HT2r7rPghKt7J6MRXVi";
+        let account = "0x6691ef8e7ebb8d7bb595a4e09263a9169d3394cc659f74b57035d5046ef0d03a";
+        let other = format!("0x{}", "11".repeat(32));
+        let set: AccountSet = serde_json::from_value(json!([ss58, other])).unwrap();
+        assert_eq!(set.account_count(), 2);
+
+        let balance = json!({"type":"map", "prefix":"XAssets AssetBalance", "key":[account, "PCX"], "value":{"Free":1}});
+        assert_eq!(set.touched("XAssets AssetBalance", &balance), vec![ss58]);
+
+        let nomination = json!({"type":"map", "prefix":"XStaking NominationRecords", "key":[other, account], "value":{}});
+        assert_eq!(
+            set.touched("XStaking NominationRecords", &nomination),
+            vec![other.clone(), ss58.to_string()]
+        );
+
+        // the key of `XSpot OrderCountOf` carries account, but not the value
+        let count =
+            json!({"type":"map", "prefix":"XSpot OrderCountOf", "key":"0x00", "value":account});
+        assert!(set.touched("XSpot OrderCountOf", &count).is_empty());
+        // the storage that doesn't carry `AccountId`
+        let now = json!({"type":"value", "prefix":"Timestamp Now", "key":null, "value":account});
+        assert!(set.touched("Timestamp Now", &now).is_empty());
+
+        // the linkage of intention isn't the account of entry
+        let intention = json!({"type":"linked_map", "prefix":"XStaking Intentions", "key":other,
+            "value":{"total_nomination":1}, "linkage":{"previous":account, "next":null}});
+        assert_eq!(
+            set.touched("XStaking Intentions", &intention),
+            vec![other.clone()]
+        );
+        // only the accounts of owners, but not the other public keys in value
+        let addr_info = json!({"type":"map", "prefix":"XMultiSig MultiSigAddrInfo", "key":other,
+            "value":{"addr_type":"Normal", "required_num":1, "owner_list":[[account, "ConfirmOnly"]]}});
+        assert_eq!(
+            set.touched("XMultiSig MultiSigAddrInfo", &addr_info),
+            vec![other.clone(), ss58.to_string()]
+        );
+        let props = json!({"type":"map", "prefix":"XAccounts IntentionPropertiesOf", "key":other,
+            "value":{"url":"", "session_key":account}});
+        assert_eq!(
+            set.touched("XAccounts IntentionPropertiesOf", &props),
+            vec![other.clone()]
+        );
+
+        assert!(serde_json::from_value::<AccountSet>(json!(["PCX"])).is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::HashSet;
+use std::convert::TryFrom;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::parse::normalize_address;
+
+/// The max count of keys of all filters of a registration.
+pub const MAX_FILTER_KEYS: usize = 100_000;
+
+/// The key filter of prefix, which is given as `{"component":<index>,"keys":[..]}`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct RawKeyFilter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    component: Option<usize>,
+    keys: Vec<Value>,
+}
+
+/// The filter that matches the decoded key of storage entry.
+///
+/// The whole key is matched if `component` is absent, otherwise the component of the tuple key
+/// is matched, e.g. `{"component":0,"keys":[<AccountId>]}` for the `(AccountId, Token)` keys.
+/// The addresses in keys can be given as SS58 (or EIP-55 for Ethereum address),
+/// which are normalized into the form of the decoded keys.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(Serialize, Deserialize)]
+#[serde(try_from = "RawKeyFilter", into = "RawKeyFilter")]
+pub struct KeyFilter {
+    component: Option<usize>,
+    keys: Vec<Value>,
+    /// The normalized keys (json string), which are used to match the keys with indexed lookup.
+    index: HashSet<String>,
+}
+
+impl TryFrom<RawKeyFilter> for KeyFilter {
+    type Error = String;
+
+    fn try_from(raw: RawKeyFilter) -> Result<Self, Self::Error> {
+        if raw.keys.is_empty() {
+            return Err("Empty keys of filter".into());
+        }
+        if raw.keys.len() > MAX_FILTER_KEYS {
+            return Err(format!("Too many keys of filter, max: {}", MAX_FILTER_KEYS));
+        }
+        let index = raw
+            .keys
+            .iter()
+            .map(|key| normalize_key(key).to_string())
+            .collect();
+        Ok(Self {
+            component: raw.component,
+            keys: raw.keys,
+            index,
+        })
+    }
+}
+
+impl From<KeyFilter> for RawKeyFilter {
+    fn from(filter: KeyFilter) -> Self {
+        Self {
+            component: filter.component,
+            keys: filter.keys,
+        }
+    }
+}
+
+impl KeyFilter {
+    /// The count of keys of the filter.
+    pub fn key_count(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Check whether the decoded key matches the filter.
+    pub fn matches(&self, key: &Value) -> bool {
+        let key = match self.component {
+            Some(index) => match key.get(index) {
+                Some(component) => component,
+                None => return false,
+            },
+            None => key,
+        };
+        self.index.contains(&key.to_string())
+    }
+}
+
+/// Normalize the addresses (SS58 or Ethereum address) and the hex strings in the key.
+fn normalize_key(key: &Value) -> Value {
+    match key {
+        Value::String(s) => match normalize_address(s) {
+            Some(address) => Value::String(address),
+            None if s.starts_with("0x") => Value::String(s.to_lowercase()),
+            None => key.clone(),
+        },
+        Value::Array(array) => Value::Array(array.iter().map(normalize_key).collect()),
+        _ => key.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_key_filter() {
+        let account = "0x6691ef8e7ebb8d7bb595a4e09263a9169d3394cc659f74b57035d5046ef0d03a";
+        let upper = format!("0x{}", account[2..].to_uppercase());
+        let filter: KeyFilter = serde_json::from_value(json!({"keys":[[upper, "PCX"]]})).unwrap();
+        assert!(filter.matches(&json!([account, "PCX"])));
+        assert!(!filter.matches(&json!([account, "BTC"])));
+
+        // SS58 address
+        let filter: KeyFilter = serde_json::from_value(
+            json!({"component":0, "keys":["5RxWcMTkfpZiZ8TQirESQhiM7hDT4HT2r7rPghKt7J6MRXVi"]}),
+        )
+        .unwrap();
+        assert!(filter.matches(&json!([account, "BTC"])));
+
+        let filter: KeyFilter =
+            serde_json::from_value(json!({"component":0, "keys":[account]})).unwrap();
+        assert!(filter.matches(&json!([account, "BTC"])));
+        assert!(!filter.matches(&json!(account)));
+        assert_eq!(
+            serde_json::to_value(&filter).unwrap(),
+            json!({"component":0, "keys":[account]})
+        );
+
+        // Ethereum address in lower case
+        let filter: KeyFilter =
+            serde_json::from_value(json!({"keys":["0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"]}))
+                .unwrap();
+        assert!(filter.matches(&json!("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed")));
+
+        assert!(serde_json::from_value::<KeyFilter>(json!({"keys":[]})).is_err());
+    }
+}
@@ -1,184 +1,888 @@
+mod account;
+mod filter;
+mod pattern;
+mod persist;
+mod predicate;
 mod push;
 mod rpc;
+mod status;
 mod util;
 
-use std::collections::{HashMap, HashSet};
-use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
 use std::sync::Arc;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use parking_lot::{Mutex, RwLock};
 use semver::Version;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 
+use self::account::AccountSet;
+use self::filter::KeyFilter;
+use self::predicate::Predicate;
+pub use self::push::Config as PushConfig;
 use self::push::{Message, PushClient};
-use crate::{BlockQueue, Result};
+use self::status::{EndedMap, SubscriptionInfo, SubscriptionState};
+use crate::store::StorageEntry;
+use crate::{Block, BlockMeta, BlockQueue, Result, SharedBlockArchive, SharedStateStore};
+
+/// The min interval of saving the acked heights of registrations.
+const SAVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The optional settings of registration.
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct RegisterOptions {
+    /// Include the raw hex of key and value (`{"key":"0x..","value":"0x.."}`) in every parsed entry.
+    pub raw: bool,
+    /// Push the snapshot of state at the block height first (only for the new registration),
+    /// and then push the blocks from the next height.
+    pub snapshot_height: Option<u64>,
+    /// The block height to start pushing from, the blocks that are no longer in the queue
+    /// are read from the block archive, default: 0.
+    pub from_height: Option<u64>,
+    /// The last block height to push, the registration is completed after it's pushed.
+    pub to_height: Option<u64>,
+    /// The key filters of prefixes, only the entries whose keys match the filter of its prefix are pushed.
+    pub filters: BTreeMap<String, KeyFilter>,
+    /// The value predicates of prefixes, only the entries whose values satisfy the predicate of its prefix are pushed.
+    pub predicates: BTreeMap<String, Predicate>,
+    /// The subscribed accounts, only the entries that carry any of the accounts are pushed
+    /// (tagged with the matching accounts) if it's not empty.
+    pub accounts: AccountSet,
+}
+
+impl RegisterOptions {
+    /// Check whether the entry with the prefix matches the key filter and the value predicate
+    /// of prefix (if any).
+    pub fn matches_entry(&self, prefix: &str, entry: &serde_json::Value) -> bool {
+        self.filters
+            .get(prefix)
+            .map_or(true, |filter| filter.matches(&entry["key"]))
+            && self
+                .predicates
+                .get(prefix)
+                .map_or(true, |predicate| predicate.eval(&entry["value"]))
+    }
+
+    /// Check the cost of the filters and the validity of the predicates,
+    /// whose prefixes must be subscribed by the prefix patterns.
+    pub fn check_filters(&self, patterns: &HashSet<String>) -> Result<()> {
+        for prefix in self.filters.keys().chain(self.predicates.keys()) {
+            pattern::check_subscribed(patterns, prefix)?;
+        }
+        let count = self
+            .filters
+            .values()
+            .map(KeyFilter::key_count)
+            .sum::<usize>()
+            + self.accounts.account_count();
+        if count > filter::MAX_FILTER_KEYS {
+            return Err(format!(
+                "Too many keys of filters and accounts, max: {}",
+                filter::MAX_FILTER_KEYS
+            )
+            .into());
+        }
+        for (prefix, predicate) in &self.predicates {
+            predicate
+                .check()
+                .map_err(|err| format!("Invalid predicate of {:?}: {}", prefix, err))?;
+        }
+        Ok(())
+    }
+}
+
+/// The change of prefixes, which takes effect from the block height.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(Serialize, Deserialize)]
+pub struct PrefixChange {
+    /// The first block height that is pushed with the changed prefixes.
+    pub height: u64,
+    pub prefixes: Vec<String>,
+    pub version: String,
+}
 
 #[derive(PartialEq, Clone, Debug)]
 struct Context {
+    /// The url that the messages are pushed to.
+    pub url: String,
     /// The prefixes of block storage that required by registrant.
     pub prefixes: HashSet<String>,
     /// The representation that used to distinguish whether the storage info matches the requirements.
     pub version: Version,
+    /// The optional settings of registration.
+    pub options: RegisterOptions,
     /// The block height of the block that has been pushed.
     pub push_height: u64,
     /// The flag the represents whether registrant deregister.
     pub deregister: bool,
+    /// The flag the represents whether the push is paused.
+    pub paused: bool,
+    /// The count of retries of the current message.
+    pub retry_count: u32,
+    /// The unix timestamp when the subscription was suspended since the push kept failing.
+    pub suspended_since: Option<u64>,
+    /// The last error of push.
+    pub last_error: Option<String>,
+    /// The block height whose message has been built but not acked yet.
+    pub pushing_height: Option<u64>,
+    /// The last change of prefixes, which is carried by the first message from its height.
+    pub prefix_change: Option<PrefixChange>,
 }
 
 impl Context {
-    pub fn new(prefixes: Vec<String>, version: Version) -> Self {
+    pub fn new(
+        url: String,
+        prefixes: Vec<String>,
+        version: Version,
+        options: RegisterOptions,
+    ) -> Self {
         Self {
+            url,
             prefixes: prefixes.iter().cloned().collect(),
             version,
-            push_height: 0,
+            push_height: options.from_height.unwrap_or(0),
+            options,
             deregister: false,
+            paused: false,
+            retry_count: 0,
+            suspended_since: None,
+            last_error: None,
+            pushing_height: None,
+            prefix_change: None,
+        }
+    }
+
+    /// The first block height that the change of context takes effect from,
+    /// i.e. the next height after the message that is being pushed.
+    pub fn effective_height(&self) -> u64 {
+        match self.pushing_height {
+            Some(height) => self.push_height.max(height + 1),
+            None => self.push_height,
         }
     }
 
     /// Update version and prefixes of the context.
-    pub fn update_prefixes(&mut self, prefixes: Vec<String>, version: Version) {
+    pub fn update_prefixes(&mut self, prefixes: Vec<String>, version: Version) -> PrefixChange {
         if version > self.version {
-            self.version = version;
-            self.prefixes.clear();
-            self.prefixes.extend(prefixes);
-            info!(
-                "New version: [{}], Updated prefixes: [{:?}]",
-                &self.version, &self.prefixes
-            );
+            info!("New version: [{}]", &version);
+            self.set_prefixes(prefixes.into_iter().collect(), version)
+        } else {
+            let mut extended = self.prefixes.clone();
+            extended.extend(prefixes);
+            let version = self.version.clone();
+            self.set_prefixes(extended, version)
+        }
+    }
+
+    /// Replace the prefixes (and the version) of the context, the change takes effect
+    /// from the effective height.
+    pub fn set_prefixes(&mut self, prefixes: HashSet<String>, version: Version) -> PrefixChange {
+        self.prefixes = prefixes;
+        self.version = version;
+        // the filters and predicates of the prefixes that are no longer subscribed are dropped
+        let patterns = &self.prefixes;
+        let subscribed = |prefix: &String| pattern::check_subscribed(patterns, prefix).is_ok();
+        self.options.filters.retain(|prefix, _| subscribed(prefix));
+        self.options
+            .predicates
+            .retain(|prefix, _| subscribed(prefix));
+        let mut sorted = self.prefixes.iter().cloned().collect::<Vec<_>>();
+        sorted.sort();
+        let change = PrefixChange {
+            height: self.effective_height(),
+            prefixes: sorted,
+            version: self.version.to_string(),
+        };
+        info!(
+            "Updated prefixes: [{:?}], version: [{}], effective height: {}",
+            &change.prefixes, &change.version, change.height
+        );
+        self.prefix_change = Some(change.clone());
+        change
+    }
+
+    /// Decide the next step of push with the max block height of the queue.
+    ///
+    /// The latest block is pushed after its next block is inserted, so the push waits
+    /// when it has caught up.
+    fn next_step(&self, max_queued_height: u64) -> PushStep {
+        if self.deregister {
+            return PushStep::Deregister;
+        }
+        let to_height = self.options.to_height;
+        if to_height.map_or(false, |to| self.push_height > to) {
+            return PushStep::Complete;
+        }
+        let end = match to_height {
+            Some(to) => max_queued_height.min(to.saturating_add(1)),
+            None => max_queued_height,
+        };
+        if self.paused || self.push_height >= end {
+            PushStep::Wait
         } else {
-            self.prefixes.extend(prefixes);
-            info!("Updated prefixes: [{:?}]", &self.prefixes);
+            PushStep::Push(self.push_height..end)
         }
     }
+
+    /// Record the failed push (after all its retries), return the next step of the push thread.
+    fn push_failed(&mut self, dead_letter_after: Option<Duration>, now: u64) -> FailureStep {
+        self.pushing_height = None;
+        let (since, step) = match self.suspended_since {
+            Some(since) => (since, FailureStep::Probe),
+            None => (
+                *self.suspended_since.get_or_insert(now),
+                FailureStep::Suspend,
+            ),
+        };
+        let suspended = Duration::from_secs(now.saturating_sub(since));
+        if dead_letter_after.map_or(false, |after| suspended >= after) {
+            FailureStep::DeadLetter
+        } else {
+            step
+        }
+    }
+
+    /// Reset the next push height, the pending change of prefixes takes effect from it.
+    pub fn reset(&mut self, height: u64) {
+        self.push_height = height;
+        self.retry_count = 0;
+        if let Some(change) = &mut self.prefix_change {
+            change.height = height;
+        }
+    }
+}
+
+/// The next step of the push thread.
+#[derive(PartialEq, Eq, Debug)]
+enum PushStep {
+    Deregister,
+    /// The last block height (`to_height`) has been pushed.
+    Complete,
+    /// Wait for the new block or the change of subscription.
+    Wait,
+    /// Push the blocks in the range.
+    Push(Range<u64>),
 }
 
-// HashMap: key - register url; value - register context.
-type RegisterMap = Arc<RwLock<HashMap<String, RegisterContext>>>;
+/// The step of the push thread after the push failed.
+#[derive(PartialEq, Eq, Debug)]
+enum FailureStep {
+    /// Suspend the subscription, whose blocks needn't be kept in the queue with the block archive.
+    Suspend,
+    /// Probe the suspended subscription again.
+    Probe,
+    /// Drop the subscription, which has been suspended for too long.
+    DeadLetter,
+}
+
+/// The ID of subscription, which is returned by `register` and carried by every pushed message.
+pub type SubscriptionId = u64;
+
+// HashMap: key - subscription id; value - register context.
+type RegisterMap = Arc<RwLock<HashMap<SubscriptionId, RegisterContext>>>;
 type RegisterContext = Arc<Mutex<Context>>;
 
 type PushSender = Sender<NotifyData>;
 type PushReceiver = Receiver<NotifyData>;
 
+#[derive(Debug, PartialEq)]
 enum NotifyData {
-    Normal((String, u64)),
-    Abnormal(String),
-    Deregister(String),
+    Normal((SubscriptionId, u64)),
+    Reset((SubscriptionId, u64)),
+    Abnormal(SubscriptionId),
+    Suspend(SubscriptionId),
+    DeadLetter(SubscriptionId),
+    Deregister(SubscriptionId),
+    Complete(SubscriptionId),
 }
 
 pub struct RegisterService {
     /// The block queue (BTreeMap: key - block height, value - json value).
     block_queue: BlockQueue,
-    /// The map of register url and register context.
+    /// The map of subscription id and register context.
     map: RegisterMap,
+    /// The id of the next subscription.
+    next_id: AtomicU64,
+    /// The ended subscriptions.
+    ended: EndedMap,
     /// PushData sender
     tx: Mutex<PushSender>,
+    /// PushData receiver, which is taken by the thread for removing block when running.
+    rx: Mutex<Option<PushReceiver>>,
+    /// The file that the registrations are persisted to.
+    registry_path: Option<PathBuf>,
+    /// The state store that serves the storage query RPC.
+    store: Option<SharedStateStore>,
+    /// The block archive that serves the replay RPC.
+    archive: Option<SharedBlockArchive>,
+    /// The retry policy of push.
+    push_config: PushConfig,
 }
 
 impl RegisterService {
     pub fn new(block_queue: BlockQueue) -> Self {
         let (tx, rx) = mpsc::channel();
-        let service = RegisterService {
+        RegisterService {
             block_queue,
             map: Default::default(),
+            next_id: AtomicU64::new(1),
+            ended: Default::default(),
             tx: Mutex::new(tx),
-        };
-        service.spawn_remove_block(rx);
-        service
+            rx: Mutex::new(Some(rx)),
+            registry_path: None,
+            store: None,
+            archive: None,
+            push_config: PushConfig::default(),
+        }
+    }
+
+    /// Persist the registrations to the file, and restore them from the file when running.
+    pub fn persist_registrations(mut self, path: PathBuf) -> Self {
+        self.registry_path = Some(path);
+        self
+    }
+
+    /// Serve the storage query RPC (`getStorage`/`listStorage`) with the state store.
+    pub fn state_store(mut self, store: SharedStateStore) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Serve the replay RPC (`replay`) with the block archive.
+    pub fn block_archive(mut self, archive: SharedBlockArchive) -> Self {
+        self.archive = Some(archive);
+        self
+    }
+
+    /// Push the messages with the retry policy.
+    pub fn push_config(mut self, config: PushConfig) -> Self {
+        self.push_config = config;
+        self
     }
 
     pub fn run(self, url: &str) -> Result<jsonrpc_http_server::Server> {
-        let io = rpc::rpc_handler(self);
+        self.check_push_config()?;
+        if let Some(rx) = self.rx.lock().take() {
+            self.spawn_remove_block(rx);
+        }
+        self.restore_registrations()?;
+        let store = self.store.clone();
+        let io = rpc::rpc_handler(self, store);
         rpc::start_http_rpc_server(url, io)
     }
 
-    fn spawn_new_push(&self, url: String, ctxt: RegisterContext, tx: PushSender) {
+    /// Without the block archive, the blocks of the suspended subscription are kept in the queue,
+    /// so the suspension must be bounded by the dead letter.
+    fn check_push_config(&self) -> Result<()> {
+        if self.archive.is_none() && self.push_config.dead_letter_after.is_none() {
+            return Err(
+                "The suspended subscription must be dead-lettered without the block archive".into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Restore the persisted registrations, and resume pushing from the last acked height.
+    fn restore_registrations(&self) -> Result<()> {
+        let path = match &self.registry_path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let registrations = persist::load(path)?;
+        let max_id = registrations.iter().map(|r| r.id).max().unwrap_or(0);
+        self.next_id.store(max_id + 1, Ordering::SeqCst);
+        for registration in registrations {
+            // the registrations that were persisted without id get new ones
+            let id = match registration.id {
+                0 => self.next_subscription_id(),
+                id => id,
+            };
+            info!(
+                "Restore Register [id: {}, url: {:?}, prefix: {:?}, version: {:?}, push height: {}]",
+                id,
+                &registration.url,
+                &registration.prefixes,
+                &registration.version,
+                registration.push_height
+            );
+            let version = Version::parse(&registration.version)?;
+            let mut ctxt = Context::new(
+                registration.url,
+                registration.prefixes,
+                version,
+                registration.options,
+            );
+            ctxt.push_height = registration.push_height;
+            ctxt.paused = registration.paused;
+            ctxt.suspended_since = registration.suspended_since;
+            ctxt.prefix_change = registration.prefix_change;
+            let ctxt = Arc::new(Mutex::new(ctxt));
+            let tx = self.tx.lock().clone();
+            self.map.write().insert(id, ctxt.clone());
+            // the snapshot (if it hasn't been pushed) is read from the state store again
+            self.spawn_new_push(id, ctxt, None, tx);
+        }
+        Ok(())
+    }
+
+    /// Check whether the blocks from the height can be pushed, i.e. they are still in the queue
+    /// or archived.
+    fn check_block_available(&self, height: u64) -> Result<()> {
+        let min_block_height = self.block_queue.read().keys().next().copied();
+        if self.archive.is_none() && min_block_height.map_or(true, |min| height < min) {
+            return Err("The block is neither in the queue nor archived".into());
+        }
+        Ok(())
+    }
+
+    /// Check the block heights of options, i.e. the blocks from `from_height` can be pushed,
+    /// which isn't after `to_height` or set with `snapshot_height`.
+    fn check_heights(&self, options: &RegisterOptions) -> Result<()> {
+        if options.snapshot_height.is_some() && options.from_height.is_some() {
+            return Err("The snapshot_height and from_height can't be set together".into());
+        }
+        if let Some(from) = options.from_height {
+            self.check_block_available(from)?;
+        }
+        if let (Some(from), Some(to)) = (options.from_height, options.to_height) {
+            if from > to {
+                return Err("Invalid block height range".into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Pause or resume pushing the blocks to the subscription.
+    fn set_paused(&self, id: SubscriptionId, paused: bool) -> Result<()> {
+        {
+            let map = self.map.read();
+            let mut ctxt = map.get(&id).ok_or("Nonexistent subscription id")?.lock();
+            ctxt.paused = paused;
+        }
+        self.block_queue.notify();
+        self.save_registrations();
+        Ok(())
+    }
+
+    /// Change the prefixes of the subscription, return the change with its effective height.
+    fn change_prefixes<F>(&self, id: SubscriptionId, f: F) -> Result<PrefixChange>
+    where
+        F: FnOnce(&mut Context) -> Result<PrefixChange>,
+    {
+        let change = {
+            let map = self.map.read();
+            let mut ctxt = map.get(&id).ok_or("Nonexistent subscription id")?.lock();
+            f(&mut ctxt)?
+        };
+        self.save_registrations();
+        Ok(change)
+    }
+
+    /// Allocate the id of new subscription.
+    fn next_subscription_id(&self) -> SubscriptionId {
+        self.next_id.fetch_add(1, Ordering::SeqCst)
+    }
+
+    /// Save the registrations to the file if the persistence is enabled.
+    fn save_registrations(&self) {
+        if let Some(path) = &self.registry_path {
+            if let Err(err) = persist::save(path, &self.map) {
+                error!("Unable to save registrations: {}", err);
+            }
+        }
+    }
+
+    fn spawn_new_push(
+        &self,
+        id: SubscriptionId,
+        ctxt: RegisterContext,
+        snapshot: Option<Vec<(String, StorageEntry)>>,
+        tx: PushSender,
+    ) {
         let queue = self.block_queue.clone();
-        let client = PushClient::new(url);
-        info!("Register: start push thread of url: [{}]", &client.url);
+        let store = self.store.clone();
+        let archive = self.archive.clone();
+        let url = ctxt.lock().url.clone();
+        let client = PushClient::with_config(url, self.push_config).report_to(ctxt.clone());
+        info!(
+            "Register: start push thread of subscription #{}, url: [{}]",
+            id, &client.url
+        );
 
-        thread::spawn(move || 'outer: loop {
-            if ctxt.lock().deregister {
-                tx.send(NotifyData::Deregister(client.url.clone()))
+        thread::spawn(move || {
+            if let Err(err) = push_snapshot(id, &client, &store, snapshot, &ctxt) {
+                tx.send(NotifyData::Abnormal(id))
                     .expect("Unable to send context");
-                info!(
-                    "Deregister: [{}] 's push thread will terminate",
-                    &client.url
+                warn!(
+                    "Push snapshot error: {}, #{} [{}] 's push thread will terminate",
+                    err, id, &client.url
                 );
-                break 'outer;
+                return;
             }
-            // Ensure that there is at least one block in the queue.
-            if queue.read().len() <= 1 {
-                thread::sleep(Duration::from_secs(1));
-                continue;
+
+            'outer: loop {
+                // taken before checking, so that the notification during the check isn't missed
+                let generation = queue.generation();
+                let step = ctxt.lock().next_step(max_queued_height(&queue));
+                let range = match step {
+                    PushStep::Deregister => {
+                        tx.send(NotifyData::Deregister(id))
+                            .expect("Unable to send context");
+                        info!(
+                            "Deregister: #{} [{}] 's push thread will terminate",
+                            id, &client.url
+                        );
+                        break 'outer;
+                    }
+                    PushStep::Complete => {
+                        tx.send(NotifyData::Complete(id))
+                            .expect("Unable to send context");
+                        info!(
+                            "Complete: #{} [{}] 's push thread will terminate",
+                            id, &client.url
+                        );
+                        break 'outer;
+                    }
+                    PushStep::Wait => {
+                        queue.wait(generation);
+                        continue;
+                    }
+                    PushStep::Push(range) => range,
+                };
+                for h in range {
+                    {
+                        let ctxt = ctxt.lock();
+                        // the subscription has been paused or reset
+                        if ctxt.paused || ctxt.push_height != h {
+                            continue 'outer;
+                        }
+                    }
+                    let msg = build_message(id, h, &queue, &archive, &ctxt);
+                    let change = msg.prefix_change().cloned();
+                    if !msg.is_empty() && client.post_big_message(msg).is_err() {
+                        let config = client.config();
+                        let (step, retry_count) = {
+                            let mut ctxt = ctxt.lock();
+                            let step = ctxt.push_failed(config.dead_letter_after, util::unix_now());
+                            (step, ctxt.retry_count)
+                        };
+                        match step {
+                            FailureStep::Suspend => {
+                                warn!(
+                                    "Suspend: #{} [{}] will be probed from block #{}",
+                                    id, &client.url, h
+                                );
+                                // the blocks can be read from the archive after resumed, so they
+                                // needn't be kept in the queue, otherwise they're kept until the
+                                // subscription is resumed or dead-lettered
+                                if archive.is_some() {
+                                    tx.send(NotifyData::Suspend(id))
+                                        .expect("Unable to send context");
+                                }
+                            }
+                            FailureStep::Probe => {}
+                            FailureStep::DeadLetter => {
+                                tx.send(NotifyData::DeadLetter(id))
+                                    .expect("Unable to send context");
+                                warn!(
+                                    "Dead letter: #{} [{}] 's push thread will terminate",
+                                    id, &client.url
+                                );
+                                break 'outer;
+                            }
+                        }
+                        thread::sleep(config.backoff(retry_count));
+                        continue 'outer;
+                    } else {
+                        let mut ctxt = ctxt.lock();
+                        ctxt.pushing_height = None;
+                        // the change has been reported unless it's replaced during the push
+                        if change.is_some() && ctxt.prefix_change == change {
+                            ctxt.prefix_change = None;
+                        }
+                        // the push height may have been reset during the push,
+                        // then the block isn't acked, and the height is lowered by the reset
+                        if ctxt.push_height == h {
+                            ctxt.push_height = h + 1; // next push height
+                            tx.send(NotifyData::Normal((id, h)))
+                                .expect("Unable to send context");
+                        }
+                    }
+                }
             }
-            let push_height = ctxt.lock().push_height;
-            let max_block_height = util::get_max_block_height(&queue);
-            for h in push_height..max_block_height {
-                let msg = match queue.read().get(&h) {
-                    Some(values) => Message::build(h, values, &ctxt.lock().prefixes),
-                    None => Message::empty(h),
+        });
+    }
+
+    /// Take the snapshot of state at the block height, which is pinned for the push thread
+    /// since the state store keeps applying the new blocks.
+    fn take_snapshot(&self, height: u64) -> Result<Vec<(String, StorageEntry)>> {
+        match &self.store {
+            Some(store) => store.read().snapshot_at(height),
+            None => Err("State store is disabled".into()),
+        }
+    }
+
+    fn spawn_replay(
+        &self,
+        url: String,
+        (from, to): (u64, u64),
+        prefixes: HashSet<String>,
+        options: RegisterOptions,
+    ) -> Result<()> {
+        let archive = self.archive.clone().ok_or("Block archive is disabled")?;
+        let client = PushClient::with_config(url, self.push_config);
+        info!(
+            "Replay: start replay thread of url: [{}], range: [{}, {}]",
+            &client.url, from, to
+        );
+
+        thread::spawn(move || {
+            for h in from..=to {
+                let msg = match archive.read().get(h) {
+                    Ok(Some(block)) => Message::build(h, &block, &prefixes, &options),
+                    Ok(None) => continue,
+                    Err(err) => {
+                        error!("Replay: unable to read block #{} from archive: {}", h, err);
+                        break;
+                    }
                 };
                 if !msg.is_empty() && client.post_big_message(msg).is_err() {
-                    tx.send(NotifyData::Abnormal(client.url.clone()))
-                        .expect("Unable to send context");
                     warn!(
-                        "Post abnormal: [{}] 's push thread will terminate",
+                        "Post abnormal: [{}] 's replay thread will terminate",
                         &client.url
                     );
-                    break 'outer;
-                } else {
-                    ctxt.lock().push_height = h + 1; // next push height
-                    tx.send(NotifyData::Normal((client.url.clone(), h)))
-                        .expect("Unable to send context");
+                    break;
                 }
             }
+            info!("Replay: [{}] 's replay thread terminated", &client.url);
         });
+        Ok(())
     }
 
     // Spawn a thread for removing pushed block for all register.
     fn spawn_remove_block(&self, rx: PushReceiver) {
         let queue = self.block_queue.clone();
         let map = self.map.clone();
+        let ended = self.ended.clone();
+        let registry_path = self.registry_path.clone();
         thread::spawn(move || {
             info!("Register service starts thread for removing block from queue");
             let mut stat = HashMap::new();
+            let mut last_save = Instant::now();
+            // whether there are acked heights that haven't been saved
+            let mut dirty = false;
             loop {
-                match rx.try_recv() {
-                    Ok(data) => remove_block_from_queue(&queue, &mut stat, &map, data),
-                    Err(TryRecvError::Empty) => thread::sleep(Duration::from_millis(50)),
-                    Err(TryRecvError::Disconnected) => {
-                        error!("Register: remove block thread terminated");
-                        break;
+                // the acked heights are saved once a second at most,
+                // so wait for the time to save them if there are any
+                let data = if dirty {
+                    let timeout = SAVE_INTERVAL
+                        .checked_sub(last_save.elapsed())
+                        .unwrap_or_default();
+                    match rx.recv_timeout(timeout) {
+                        Ok(data) => Some(data),
+                        Err(RecvTimeoutError::Timeout) => None,
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                } else {
+                    match rx.recv() {
+                        Ok(data) => Some(data),
+                        Err(_) => break,
+                    }
+                };
+                let force = match data {
+                    Some(data) => {
+                        let force = !matches!(data, NotifyData::Normal(_));
+                        remove_block_from_queue(&queue, &mut stat, &map, &ended, data);
+                        force
+                    }
+                    None => true,
+                };
+                if let Some(path) = &registry_path {
+                    if force || last_save.elapsed() >= SAVE_INTERVAL {
+                        if let Err(err) = persist::save(path, &map) {
+                            error!("Unable to save registrations: {}", err);
+                        }
+                        last_save = Instant::now();
+                        dirty = false;
+                    } else {
+                        dirty = true;
                     }
                 }
             }
+            error!("Register: remove block thread terminated");
         });
     }
 }
 
+/// Push the snapshot of state at the block height (the `snapshot_height` option) to the registrant,
+/// and then the blocks are pushed from the next height.
+/// The snapshot taken by the registration is pushed if any, otherwise it's read from the state store.
+fn push_snapshot(
+    id: SubscriptionId,
+    client: &PushClient,
+    store: &Option<SharedStateStore>,
+    snapshot: Option<Vec<(String, StorageEntry)>>,
+    ctxt: &RegisterContext,
+) -> Result<()> {
+    let (snapshot_height, push_height) = {
+        let ctxt = ctxt.lock();
+        (ctxt.options.snapshot_height, ctxt.push_height)
+    };
+    let height = match snapshot_height {
+        // the snapshot has been pushed before the restart
+        Some(height) if push_height <= height => height,
+        _ => return Ok(()),
+    };
+    let entries = match snapshot {
+        Some(entries) => entries,
+        None => store
+            .as_ref()
+            .ok_or("State store is disabled")?
+            .read()
+            .snapshot_at(height)?,
+    };
+    let values = entries
+        .into_iter()
+        .map(|(prefix, entry)| {
+            json!({
+                "type": "snapshot",
+                "prefix": prefix,
+                "key": entry.key,
+                "value": entry.value,
+                "height": entry.height,
+            })
+        })
+        .collect();
+    let block = Block {
+        meta: BlockMeta::default(),
+        values,
+        raw: vec![],
+    };
+    let msg = {
+        let ctxt = ctxt.lock();
+        Message::build(height, &block, &ctxt.prefixes, &ctxt.options).with_subscription(id)
+    };
+    info!(
+        "Push snapshot at block #{} to [{}], entries: {}",
+        height,
+        &client.url,
+        block.values.len()
+    );
+    if !msg.is_empty() {
+        client.post_big_message(msg)?;
+    }
+    ctxt.lock().push_height = height + 1;
+    Ok(())
+}
+
+/// Build the message of the block at the height for the subscription,
+/// the block that is no longer in the queue is read from the block archive.
+fn build_message(
+    id: SubscriptionId,
+    h: u64,
+    queue: &BlockQueue,
+    archive: &Option<SharedBlockArchive>,
+    ctxt: &RegisterContext,
+) -> Message {
+    let build = |block: Option<&Block>| {
+        let mut ctxt = ctxt.lock();
+        ctxt.pushing_height = Some(h);
+        let msg = match block {
+            Some(block) => Message::build(h, block, &ctxt.prefixes, &ctxt.options),
+            None => {
+                warn!("Block #{} is neither in the queue nor archived", h);
+                Message::empty(h)
+            }
+        };
+        let change = ctxt.prefix_change.clone();
+        msg.with_subscription(id)
+            .with_prefix_change(change.filter(|change| change.height <= h))
+    };
+    let queued = queue.read().get(&h).map(|block| build(Some(block)));
+    match queued {
+        Some(msg) => msg,
+        // the block has been removed from the queue
+        None => build(read_archived_block(archive, h).as_ref()),
+    }
+}
+
+/// The max block height of the queue, 0 if the queue is empty.
+fn max_queued_height(queue: &BlockQueue) -> u64 {
+    queue.read().keys().next_back().copied().unwrap_or(0)
+}
+
+/// Read the block that is no longer in the queue from the block archive.
+fn read_archived_block(archive: &Option<SharedBlockArchive>, height: u64) -> Option<Block> {
+    let archive = archive.as_ref()?;
+    match archive.read().get(height) {
+        Ok(block) => block,
+        Err(err) => {
+            error!("Unable to read block #{} from archive: {}", height, err);
+            None
+        }
+    }
+}
+
 fn remove_block_from_queue(
     queue: &BlockQueue,
-    stat: &mut HashMap<String, u64>,
+    stat: &mut HashMap<SubscriptionId, Option<u64>>,
     map: &RegisterMap,
+    ended: &EndedMap,
     data: NotifyData,
 ) {
+    let mut remove = |id: SubscriptionId, state: SubscriptionState| {
+        stat.remove(&id);
+        let removed = map.write().remove(&id);
+        if let Some(ctxt) = removed {
+            let info = SubscriptionInfo::new(id, &ctxt.lock(), max_queued_height(queue));
+            status::record_ended(ended, info, state);
+        }
+    };
     match data {
-        NotifyData::Normal((url, push_height)) => {
-            stat.entry(url)
-                .and_modify(|height| *height = push_height)
-                .or_insert(push_height);
-        }
-        NotifyData::Abnormal(url) => {
-            info!("Abnormal, remove register [{}]", &url);
-            stat.remove(&url);
-            map.write().remove(&url);
+        NotifyData::Normal((id, push_height)) => {
+            stat.insert(id, Some(push_height));
+        }
+        NotifyData::Reset((id, height)) => {
+            info!(
+                "Reset, lower the push height of register #{} to block #{}",
+                id, height
+            );
+            // the blocks from the reset height are retained until they're pushed again,
+            // i.e. nothing is acked if it's reset to block #0
+            stat.insert(id, height.checked_sub(1));
+            return;
+        }
+        NotifyData::Abnormal(id) => {
+            info!("Abnormal, remove register #{}", id);
+            remove(id, SubscriptionState::Dropped);
+            return;
+        }
+        NotifyData::Suspend(id) => {
+            info!("Suspend, ignore the push height of register #{}", id);
+            stat.remove(&id);
             return;
         }
-        NotifyData::Deregister(url) => {
-            info!("Deregister, remove register [{}]", &url);
-            stat.remove(&url);
-            map.write().remove(&url);
+        NotifyData::DeadLetter(id) => {
+            info!("Dead letter, remove register #{}", id);
+            remove(id, SubscriptionState::DeadLettered);
+            return;
+        }
+        NotifyData::Deregister(id) => {
+            info!("Deregister, remove register #{}", id);
+            remove(id, SubscriptionState::Deregistered);
+            return;
+        }
+        NotifyData::Complete(id) => {
+            info!("Complete, remove register #{}", id);
+            remove(id, SubscriptionState::Completed);
             return;
         }
     }
@@ -188,7 +892,9 @@ fn remove_block_from_queue(
     let max_block_height = util::get_max_block_height(queue);
     let min_block_height = util::get_min_block_height(queue);
     let min_push_height = match stat.values().min() {
-        Some(height) => *height,
+        Some(Some(height)) => *height,
+        // some subscription hasn't acked any block, so all blocks are retained
+        Some(None) => return,
         None => 0,
     };
     assert!(min_push_height < max_block_height);
@@ -206,12 +912,15 @@ fn remove_block_from_queue(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::StateStore;
 
     #[test]
     fn test_context_update_prefixes() {
         let mut ctxt = Context::new(
+            "http://127.0.0.1:12345".into(),
             vec!["Balances FreeBalance1".into()],
             Version::parse("1.0.0").unwrap(),
+            RegisterOptions::default(),
         );
         ctxt.update_prefixes(
             vec!["Balances FreeBalance2".into()],
@@ -240,4 +949,406 @@ mod tests {
         assert_eq!(ctxt.prefixes.contains("Balances FreeBalance4"), true);
         assert_eq!(ctxt.version, Version::new(1, 1, 0));
     }
+
+    #[test]
+    fn test_context_prefix_change_height() {
+        let mut ctxt = Context::new(
+            "http://127.0.0.1:12345".into(),
+            vec!["Balances FreeBalance1".into()],
+            Version::parse("1.0.0").unwrap(),
+            RegisterOptions::default(),
+        );
+        ctxt.push_height = 10;
+        let change = ctxt.set_prefixes(HashSet::new(), Version::new(1, 0, 0));
+        assert_eq!(change.height, 10);
+        assert!(change.prefixes.is_empty());
+
+        // the message of block #10 has been built with the old prefixes
+        ctxt.pushing_height = Some(10);
+        let change = ctxt.update_prefixes(
+            vec!["Balances FreeBalance2".into()],
+            Version::parse("1.0.0").unwrap(),
+        );
+        assert_eq!(change.height, 11);
+        assert_eq!(change.prefixes, vec!["Balances FreeBalance2".to_string()]);
+        assert_eq!(ctxt.prefix_change, Some(change));
+
+        ctxt.reset(5);
+        assert_eq!(
+            ctxt.prefix_change.as_ref().map(|change| change.height),
+            Some(5)
+        );
+    }
+
+    fn context(options: RegisterOptions) -> Context {
+        Context::new(
+            "http://127.0.0.1:12345".into(),
+            vec!["aaa".into()],
+            Version::new(1, 0, 0),
+            options,
+        )
+    }
+
+    #[test]
+    fn test_context_next_step() {
+        let options = RegisterOptions {
+            from_height: Some(10),
+            to_height: Some(20),
+            ..Default::default()
+        };
+        let mut ctxt = context(options);
+        assert_eq!(ctxt.push_height, 10);
+        // the queue hasn't reached the from height
+        assert_eq!(ctxt.next_step(0), PushStep::Wait);
+        assert_eq!(ctxt.next_step(10), PushStep::Wait);
+        // the latest block is pushed after its next block is inserted
+        assert_eq!(ctxt.next_step(15), PushStep::Push(10..15));
+        // the push is bounded by the to height
+        assert_eq!(ctxt.next_step(100), PushStep::Push(10..21));
+
+        ctxt.paused = true;
+        assert_eq!(ctxt.next_step(100), PushStep::Wait);
+        ctxt.paused = false;
+
+        ctxt.push_height = 21;
+        assert_eq!(ctxt.next_step(100), PushStep::Complete);
+
+        ctxt.deregister = true;
+        assert_eq!(ctxt.next_step(100), PushStep::Deregister);
+    }
+
+    #[test]
+    fn test_options_check_filters() {
+        let patterns = ["XAssets *".to_string()].iter().cloned().collect();
+        let options = |options: serde_json::Value| {
+            serde_json::from_value::<RegisterOptions>(options).unwrap()
+        };
+        let filters =
+            json!({"filters": {"XAssets AssetBalance": {"component": 0, "keys": ["0x02"]}}});
+        assert!(options(filters).check_filters(&patterns).is_ok());
+        let predicates = json!({"predicates": {"XAssets AssetBalance": {"gt": ["/Free", 0]}}});
+        assert!(options(predicates).check_filters(&patterns).is_ok());
+
+        // the typo, or the unsubscribed prefix
+        let filters =
+            json!({"filters": {"XAssets AssetBalence": {"component": 0, "keys": ["0x02"]}}});
+        assert!(options(filters).check_filters(&patterns).is_err());
+        let predicates = json!({"predicates": {"XStaking Intentions": {"exists": "/jackpot"}}});
+        assert!(options(predicates).check_filters(&patterns).is_err());
+    }
+
+    #[test]
+    fn test_replay_check_prefixes() {
+        use super::rpc::RegisterApi;
+
+        let service = RegisterService::new(BlockQueue::default());
+        let replay = |prefixes: Vec<&str>, options: serde_json::Value| {
+            service.replay(
+                prefixes.into_iter().map(String::from).collect(),
+                "http://127.0.0.1:12345".into(),
+                0,
+                10,
+                Some(serde_json::from_value(options).unwrap()),
+            )
+        };
+        let err = replay(vec!["XAssets AssetBalence"], json!({})).unwrap_err();
+        assert!(err.to_string().contains("Unknown prefix pattern"));
+        let options = json!({"predicates": {"XAssets AssetInfo": {"exists": "/0"}}});
+        let err = replay(vec!["XAssets AssetBalance"], options).unwrap_err();
+        assert!(err.to_string().contains("Unsubscribed prefix"));
+        // the valid replay is rejected only since the block archive is disabled
+        let err = replay(vec!["XAssets AssetBalance"], json!({})).unwrap_err();
+        assert!(err.to_string().contains("Block archive is disabled"));
+    }
+
+    #[test]
+    fn test_context_push_failed() {
+        let after = Some(Duration::from_secs(100));
+        let mut ctxt = context(RegisterOptions::default());
+        ctxt.pushing_height = Some(0);
+        assert_eq!(ctxt.push_failed(after, 1000), FailureStep::Suspend);
+        assert_eq!(ctxt.pushing_height, None);
+        assert_eq!(ctxt.suspended_since, Some(1000));
+        assert_eq!(ctxt.push_failed(after, 1099), FailureStep::Probe);
+        assert_eq!(ctxt.suspended_since, Some(1000));
+        assert_eq!(ctxt.push_failed(after, 1100), FailureStep::DeadLetter);
+        assert_eq!(ctxt.push_failed(None, 1100), FailureStep::Probe);
+
+        // resumed
+        ctxt.suspended_since = None;
+        assert_eq!(ctxt.push_failed(after, 2000), FailureStep::Suspend);
+        assert_eq!(ctxt.suspended_since, Some(2000));
+    }
+
+    #[test]
+    fn test_check_push_config() {
+        let config = PushConfig {
+            dead_letter_after: None,
+            ..Default::default()
+        };
+        let service = RegisterService::new(BlockQueue::default()).push_config(config);
+        assert!(service.check_push_config().is_err());
+        let service = RegisterService::new(BlockQueue::default());
+        assert!(service.check_push_config().is_ok());
+    }
+
+    #[test]
+    fn test_build_message_from_queue_or_archive() {
+        let dir = std::env::temp_dir().join(format!("register-archive-{}", std::process::id()));
+        let block = |value: u64| Block::new(vec![json!({"prefix":"aaa", "value":value})]);
+        let mut archive = crate::BlockArchive::open(&dir).unwrap();
+        archive.append(1, &block(1)).unwrap();
+        archive.append(2, &block(2)).unwrap();
+        let archive = Some(Arc::new(RwLock::new(archive)));
+
+        let queue = BlockQueue::default();
+        queue.write().insert(2, block(20));
+        queue.write().insert(3, block(3));
+        let ctxt = Arc::new(Mutex::new(context(RegisterOptions::default())));
+
+        let data = |msg: Message| json!(msg)["data"].clone();
+        // the queued block is preferred
+        let msg = build_message(1, 2, &queue, &archive, &ctxt);
+        assert_eq!(data(msg), json!([{"prefix":"aaa", "value":20}]));
+        assert_eq!(ctxt.lock().pushing_height, Some(2));
+        // the block that is no longer in the queue
+        let msg = build_message(1, 1, &queue, &archive, &ctxt);
+        assert_eq!(data(msg), json!([{"prefix":"aaa", "value":1}]));
+        // the block that is neither in the queue nor archived
+        assert!(build_message(1, 0, &queue, &archive, &ctxt).is_empty());
+        assert!(build_message(1, 1, &queue, &None, &ctxt).is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_register_check_heights() {
+        use super::rpc::RegisterApi;
+
+        let queue = BlockQueue::default();
+        queue.write().insert(100, Block::new(vec![]));
+        let service = RegisterService::new(queue);
+        let register = |options: serde_json::Value| {
+            service.register(
+                vec!["Timestamp Now".into()],
+                "http://127.0.0.1:12345".into(),
+                "1.0.0".into(),
+                Some(serde_json::from_value(options).unwrap()),
+            )
+        };
+        // the blocks before the queue aren't archived
+        assert!(register(json!({"from_height": 99})).is_err());
+        assert!(register(json!({"from_height": 100, "to_height": 99})).is_err());
+        assert!(register(json!({"from_height": 100, "snapshot_height": 100})).is_err());
+        assert!(service.map.read().is_empty());
+
+        // the updated options are checked the same way
+        let id = register(json!({"from_height": 100})).unwrap();
+        let update = |options: serde_json::Value| {
+            service.update(
+                id,
+                vec!["Timestamp Now".into()],
+                "1.0.0".into(),
+                Some(serde_json::from_value(options).unwrap()),
+            )
+        };
+        assert!(update(json!({"from_height": 99})).is_err());
+        assert!(update(json!({"from_height": 100, "to_height": 99})).is_err());
+        assert!(update(json!({"from_height": 100, "snapshot_height": 100})).is_err());
+        assert!(update(json!({"from_height": 100, "to_height": 200})).is_ok());
+        assert_eq!(service.map.read()[&id].lock().options.to_height, Some(200));
+    }
+
+    #[test]
+    fn test_update_drop_unsubscribed_filters() {
+        use super::rpc::RegisterApi;
+
+        let service = RegisterService::new(BlockQueue::default());
+        let options = json!({
+            "filters": {"XAssets AssetBalance": {"component": 0, "keys": ["0x02"]}},
+            "predicates": {"Timestamp Now": {"gt": ["", 0]}},
+        });
+        let id = service
+            .register(
+                vec!["XAssets AssetBalance".into(), "Timestamp Now".into()],
+                "http://127.0.0.1:12345".into(),
+                "1.0.0".into(),
+                Some(serde_json::from_value(options).unwrap()),
+            )
+            .unwrap();
+        // the prefixes are replaced by the newer version, and the options are kept
+        service
+            .update(id, vec!["Timestamp Now".into()], "1.1.0".into(), None)
+            .unwrap();
+        let options = service.map.read()[&id].lock().options.clone();
+        assert!(options.filters.is_empty());
+        assert!(options.predicates.contains_key("Timestamp Now"));
+    }
+
+    #[test]
+    fn test_take_snapshot() {
+        let service = RegisterService::new(BlockQueue::default());
+        assert!(service.take_snapshot(1).is_err());
+
+        let mut store = StateStore::new();
+        let value = json!({"prefix": "Timestamp Now", "key": null, "value": 1});
+        store.apply_block(1, &[value]).unwrap();
+        let store = Arc::new(RwLock::new(store));
+        let service = service.state_store(store.clone());
+        // the snapshot is taken at once, and it isn't changed by the later blocks
+        let snapshot = service.take_snapshot(1).unwrap();
+        let value = json!({"prefix": "Timestamp Now", "key": null, "value": 2});
+        store.write().apply_block(2, &[value]).unwrap();
+        assert_eq!(snapshot.len(), 1);
+        assert_eq!(snapshot[0].1.value, json!(1));
+        // the past snapshot requires the storage history
+        assert!(service.take_snapshot(1).is_err());
+        assert!(service.take_snapshot(3).is_err());
+    }
+
+    #[test]
+    fn test_subscriptions_of_same_url() {
+        use super::rpc::RegisterApi;
+
+        let service = RegisterService::new(BlockQueue::default());
+        let register = |prefix: &str| {
+            service
+                .register(
+                    vec![prefix.into()],
+                    "http://127.0.0.1:12345".into(),
+                    "1.0.0".into(),
+                    None,
+                )
+                .unwrap()
+        };
+        let id1 = register("Timestamp Now");
+        let id2 = register("XAssets AssetBalance");
+        assert_ne!(id1, id2);
+        assert_eq!(service.map.read().len(), 2);
+
+        let prefixes = |id: SubscriptionId| service.map.read()[&id].lock().prefixes.clone();
+        service
+            .update(id2, vec!["XAssets AssetInfo".into()], "1.0.0".into(), None)
+            .unwrap();
+        assert_eq!(prefixes(id1).len(), 1);
+        assert!(prefixes(id2).contains("XAssets AssetBalance"));
+        assert!(prefixes(id2).contains("XAssets AssetInfo"));
+
+        service.deregister(id1).unwrap();
+        assert!(service.map.read()[&id1].lock().deregister);
+        assert!(!service.map.read()[&id2].lock().deregister);
+
+        // nonexistent subscription id
+        assert!(service.update(100, vec![], "1.0.0".into(), None).is_err());
+        assert!(service.deregister(100).is_err());
+    }
+
+    #[test]
+    fn test_manage_subscription() {
+        use super::rpc::RegisterApi;
+
+        let queue = BlockQueue::default();
+        for h in 100..=102 {
+            queue.write().insert(h, Block::new(vec![]));
+        }
+        let service = RegisterService::new(queue);
+        let rx = service.rx.lock().take().unwrap();
+        // not spawning the push thread, so that the state isn't changed by push
+        let options = RegisterOptions {
+            from_height: Some(100),
+            to_height: Some(101),
+            ..Default::default()
+        };
+        service
+            .map
+            .write()
+            .insert(1, Arc::new(Mutex::new(context(options))));
+
+        let info = service.get_subscription(1).unwrap();
+        assert_eq!(info.state, SubscriptionState::Active);
+        assert_eq!(info.push_height, 100);
+        assert_eq!(info.lag, 2);
+        assert!(service.get_subscription(2).is_err());
+
+        service.pause_subscription(1).unwrap();
+        assert_eq!(
+            service.get_subscription(1).unwrap().state,
+            SubscriptionState::Paused
+        );
+        service.resume_subscription(1).unwrap();
+        assert_eq!(
+            service.get_subscription(1).unwrap().state,
+            SubscriptionState::Active
+        );
+        assert!(service.pause_subscription(2).is_err());
+
+        service.reset_subscription(1, 101).unwrap();
+        assert_eq!(service.get_subscription(1).unwrap().push_height, 101);
+        assert_eq!(rx.try_recv(), Ok(NotifyData::Reset((1, 101))));
+        // the block isn't available, or beyond the to_height
+        assert!(service.reset_subscription(1, 99).is_err());
+        assert!(service.reset_subscription(1, 102).is_err());
+        assert!(service.reset_subscription(2, 101).is_err());
+        assert!(rx.try_recv().is_err());
+
+        service.ended.write().insert(
+            3,
+            SubscriptionInfo {
+                state: SubscriptionState::Completed,
+                ..service.get_subscription(1).unwrap()
+            },
+        );
+        let list = service.list_subscriptions().unwrap();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[1].state, SubscriptionState::Completed);
+    }
+
+    #[test]
+    fn test_remove_block_after_reset() {
+        let queue = BlockQueue::default();
+        for h in 1..=5 {
+            queue.write().insert(h, Block::new(vec![]));
+        }
+        let map = RegisterMap::default();
+        let ended = EndedMap::default();
+        let mut stat = HashMap::new();
+        let mut notify = |data| remove_block_from_queue(&queue, &mut stat, &map, &ended, data);
+        notify(NotifyData::Normal((1, 2)));
+        notify(NotifyData::Normal((2, 4)));
+        // the subscription #2 is reset to block 3, which is retained until it's pushed again
+        notify(NotifyData::Reset((2, 3)));
+        notify(NotifyData::Normal((1, 4)));
+        assert_eq!(
+            queue.read().keys().copied().collect::<Vec<_>>(),
+            vec![3, 4, 5]
+        );
+        notify(NotifyData::Normal((2, 3)));
+        assert_eq!(queue.read().keys().copied().collect::<Vec<_>>(), vec![4, 5]);
+    }
+
+    #[test]
+    fn test_remove_block_after_reset_to_genesis() {
+        let queue = BlockQueue::default();
+        for h in 0..=3 {
+            queue.write().insert(h, Block::new(vec![]));
+        }
+        let map = RegisterMap::default();
+        let ended = EndedMap::default();
+        let mut stat = HashMap::new();
+        let mut notify = |data| remove_block_from_queue(&queue, &mut stat, &map, &ended, data);
+        // the subscription #2 is reset to block 0 before it acks anything
+        notify(NotifyData::Reset((2, 0)));
+        notify(NotifyData::Normal((1, 1)));
+        assert_eq!(
+            queue.read().keys().copied().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3]
+        );
+        notify(NotifyData::Normal((2, 0)));
+        assert_eq!(
+            queue.read().keys().copied().collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        notify(NotifyData::Normal((2, 1)));
+        assert_eq!(queue.read().keys().copied().collect::<Vec<_>>(), vec![2, 3]);
+    }
 }
@@ -0,0 +1,200 @@
+//! The prefix patterns of registration.
+//!
+//! Besides the exact prefix, the registrant can subscribe the prefixes by the glob pattern
+//! (`*` matches any characters, `?` matches one character), e.g. `XStaking *` for the whole module,
+//! and exclude the prefixes by the pattern with leading `!`, e.g. `!XStaking NominationRecords*`.
+
+use std::collections::HashSet;
+
+use crate::derive::DERIVED_PREFIXES;
+use crate::parse::{event_prefixes, RuntimeStorage, ERROR_PREFIX, UNKNOWN_PREFIX};
+use crate::{Error, Result};
+
+const EXCLUSION: char = '!';
+/// The max count of suggested prefixes for the unknown pattern.
+const MAX_SUGGESTIONS: usize = 3;
+
+fn is_glob(pattern: &str) -> bool {
+    pattern.contains(|c| c == '*' || c == '?')
+}
+
+/// Check whether the prefix matches the prefix patterns of registration,
+/// i.e. it matches any inclusive pattern and none of the exclusive patterns.
+pub fn matches(patterns: &HashSet<String>, prefix: &str) -> bool {
+    let included = patterns.contains(prefix)
+        || patterns.iter().any(|pattern| {
+            !pattern.starts_with(EXCLUSION) && is_glob(pattern) && glob_match(pattern, prefix)
+        });
+    included
+        && !patterns
+            .iter()
+            .any(|pattern| pattern.starts_with(EXCLUSION) && glob_match(&pattern[1..], prefix))
+}
+
+/// All prefixes that can be subscribed: the runtime storages, the pseudo prefixes,
+/// the derived entries and the events.
+fn known_prefixes() -> Vec<String> {
+    RuntimeStorage::prefixes()
+        .into_iter()
+        .chain(vec![UNKNOWN_PREFIX, ERROR_PREFIX])
+        .chain(DERIVED_PREFIXES.iter().copied())
+        .map(String::from)
+        .chain(event_prefixes())
+        .collect()
+}
+
+/// Check that every pattern matches at least one known prefix,
+/// the error lists the close matches of the unknown pattern.
+pub fn check_patterns(patterns: &[String]) -> Result<()> {
+    let known = known_prefixes();
+    for pattern in patterns {
+        let body = pattern.strip_prefix(EXCLUSION).unwrap_or(pattern);
+        if !known.iter().any(|prefix| glob_match(body, prefix)) {
+            return Err(unknown("prefix pattern", pattern, body, &known));
+        }
+    }
+    Ok(())
+}
+
+/// Check that the prefix (e.g. the key of filters) is a known prefix,
+/// and it's subscribed by the prefix patterns.
+pub fn check_subscribed(patterns: &HashSet<String>, prefix: &str) -> Result<()> {
+    let known = known_prefixes();
+    if !known.iter().any(|known| known == prefix) {
+        return Err(unknown("prefix", prefix, prefix, &known));
+    }
+    if !matches(patterns, prefix) {
+        return Err(format!("Unsubscribed prefix: {:?}", prefix).into());
+    }
+    Ok(())
+}
+
+/// The error of the unknown prefix (pattern), which lists the close matches of it.
+fn unknown(kind: &str, pattern: &str, body: &str, known: &[String]) -> Error {
+    let mut candidates = known
+        .iter()
+        .map(|prefix| (levenshtein(body, prefix), prefix))
+        .filter(|(distance, _)| *distance <= (body.chars().count() / 3).max(2))
+        .collect::<Vec<_>>();
+    candidates.sort();
+    let suggestions = candidates
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, prefix)| prefix.as_str())
+        .collect::<Vec<_>>();
+    let message = if suggestions.is_empty() {
+        format!("Unknown {} {:?}", kind, pattern)
+    } else {
+        format!(
+            "Unknown {} {:?}, did you mean: {:?}",
+            kind, pattern, suggestions
+        )
+    };
+    message.into()
+}
+
+/// Match the text with the glob pattern (`*` and `?`).
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut p, mut t) = (0, 0);
+    // the position of the last `*` in pattern and the matched position in text
+    let mut star = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// The edit distance between two strings.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            cur[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("XStaking *", "XStaking Intentions"));
+        assert!(glob_match("XAssets Asset*", "XAssets AssetBalance"));
+        assert!(glob_match(
+            "XStaking NominationRecords?1",
+            "XStaking NominationRecordsV1"
+        ));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("XStaking *", "XAssets AssetBalance"));
+        assert!(!glob_match("XAssets", "XAssets AssetBalance"));
+    }
+
+    #[test]
+    fn test_matches() {
+        let patterns = [
+            "XStaking *",
+            "!XStaking NominationRecords*",
+            "XAssets AssetBalance",
+        ]
+        .iter()
+        .map(|p| p.to_string())
+        .collect::<HashSet<_>>();
+        assert!(matches(&patterns, "XAssets AssetBalance"));
+        assert!(matches(&patterns, "XStaking Intentions"));
+        assert!(!matches(&patterns, "XStaking NominationRecordsV1"));
+        assert!(!matches(&patterns, "XAssets AssetInfo"));
+    }
+
+    #[test]
+    fn test_check_patterns() {
+        let patterns = vec![
+            "XStaking *".to_string(),
+            "!XStaking NominationRecords*".to_string(),
+            "XAssets AssetBalance".to_string(),
+            "$unknown".to_string(),
+            "XAssets.Move".to_string(),
+        ];
+        assert!(check_patterns(&patterns).is_ok());
+
+        let err = check_patterns(&["XAssets AssetBalence".to_string()]).unwrap_err();
+        assert!(err.to_string().contains("\"XAssets AssetBalance\""));
+        assert!(check_patterns(&["Foo *".to_string()]).is_err());
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_check_subscribed() {
+        let patterns = ["XStaking *", "!XStaking NominationRecords*"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<HashSet<_>>();
+        assert!(check_subscribed(&patterns, "XStaking Intentions").is_ok());
+
+        let err = check_subscribed(&patterns, "XStaking Intentionz").unwrap_err();
+        assert!(err.to_string().contains("\"XStaking Intentions\""));
+        assert!(check_subscribed(&patterns, "XStaking *").is_err());
+        assert!(check_subscribed(&patterns, "XStaking NominationRecords").is_err());
+        assert!(check_subscribed(&patterns, "XAssets AssetBalance").is_err());
+    }
+}
@@ -0,0 +1,126 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Context, PrefixChange, RegisterMap, RegisterOptions, SubscriptionId};
+use crate::Result;
+
+/// The persisted registration, which is restored after restart.
+#[derive(PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize)]
+pub struct Registration {
+    /// The id of subscription, 0 for the registration persisted before the id is introduced.
+    #[serde(default)]
+    pub id: SubscriptionId,
+    pub url: String,
+    pub prefixes: Vec<String>,
+    pub version: String,
+    pub options: RegisterOptions,
+    /// The next block height to push (the last acked height + 1).
+    pub push_height: u64,
+    #[serde(default)]
+    pub paused: bool,
+    /// The unix timestamp when the subscription was suspended.
+    #[serde(default)]
+    pub suspended_since: Option<u64>,
+    /// The change of prefixes that hasn't been reported by message yet.
+    #[serde(default)]
+    pub prefix_change: Option<PrefixChange>,
+}
+
+impl Registration {
+    fn new(id: SubscriptionId, ctxt: &Context) -> Self {
+        let mut prefixes = ctxt.prefixes.iter().cloned().collect::<Vec<_>>();
+        prefixes.sort();
+        Self {
+            id,
+            url: ctxt.url.clone(),
+            prefixes,
+            version: ctxt.version.to_string(),
+            options: ctxt.options.clone(),
+            push_height: ctxt.push_height,
+            paused: ctxt.paused,
+            suspended_since: ctxt.suspended_since,
+            prefix_change: ctxt.prefix_change.clone(),
+        }
+    }
+}
+
+/// Save all registrations (except the deregistered ones) to the file
+/// (write to a temporary file and then rename it).
+pub fn save(path: &Path, map: &RegisterMap) -> Result<()> {
+    let mut registrations = map
+        .read()
+        .iter()
+        .map(|(id, ctxt)| (*id, ctxt.lock()))
+        .filter(|(_, ctxt)| !ctxt.deregister)
+        .map(|(id, ctxt)| Registration::new(id, &ctxt))
+        .collect::<Vec<_>>();
+    registrations.sort_by_key(|registration| registration.id);
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, serde_json::to_vec_pretty(&registrations)?)?;
+    fs::rename(&tmp, path)?;
+    debug!("Save {} registrations to {:?}", registrations.len(), path);
+    Ok(())
+}
+
+/// Load the registrations from the file, empty if the file doesn't exist.
+pub fn load(path: &Path) -> Result<Vec<Registration>> {
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let registrations: Vec<Registration> = serde_json::from_slice(&fs::read(path)?)?;
+    info!("Load {} registrations from {:?}", registrations.len(), path);
+    Ok(registrations)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use parking_lot::{Mutex, RwLock};
+    use semver::Version;
+
+    use super::*;
+
+    #[test]
+    fn test_save_and_load_registrations() {
+        let path = std::env::temp_dir().join(format!("registrations-{}.json", std::process::id()));
+        let mut ctxt = Context::new(
+            "http://127.0.0.1:12345".into(),
+            vec!["XAssets AssetBalance".into(), "Timestamp Now".into()],
+            Version::parse("1.0.0").unwrap(),
+            RegisterOptions::default(),
+        );
+        ctxt.push_height = 100;
+        let mut deregistered = ctxt.clone();
+        deregistered.deregister = true;
+        let map: RegisterMap = Arc::new(RwLock::new(HashMap::new()));
+        map.write().insert(1, Arc::new(Mutex::new(ctxt)));
+        map.write().insert(2, Arc::new(Mutex::new(deregistered)));
+
+        save(&path, &map).unwrap();
+        assert_eq!(
+            load(&path).unwrap(),
+            vec![Registration {
+                id: 1,
+                url: "http://127.0.0.1:12345".into(),
+                prefixes: vec!["Timestamp Now".into(), "XAssets AssetBalance".into()],
+                version: "1.0.0".into(),
+                options: RegisterOptions::default(),
+                push_height: 100,
+                paused: false,
+                suspended_since: None,
+                prefix_change: None,
+            }]
+        );
+        fs::remove_file(&path).unwrap();
+        assert_eq!(load(&path).unwrap(), vec![]);
+    }
+}
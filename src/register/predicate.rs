@@ -0,0 +1,156 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// The max count of nodes of a predicate.
+pub const MAX_PREDICATE_NODES: usize = 256;
+
+/// The predicate over the decoded value of storage entry, e.g.
+/// `{"and":[{"eq":["/status","Inactive"]},{"gt":["/total_nomination",100000]}]}`.
+///
+/// The path is the JSON pointer (RFC 6901) into the value, the comparison is `false`
+/// if the path doesn't exist, the numbers are compared by their numeric values,
+/// and the strings are compared lexicographically.
+#[derive(PartialEq, Eq, Clone, Debug)]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Predicate {
+    And(Vec<Predicate>),
+    Or(Vec<Predicate>),
+    Not(Box<Predicate>),
+    Exists(String),
+    Eq(String, Value),
+    Ne(String, Value),
+    Gt(String, Value),
+    Gte(String, Value),
+    Lt(String, Value),
+    Lte(String, Value),
+    In(String, Vec<Value>),
+}
+
+impl Predicate {
+    /// Evaluate the predicate over the value.
+    pub fn eval(&self, value: &Value) -> bool {
+        use self::Predicate::*;
+        match self {
+            And(predicates) => predicates.iter().all(|p| p.eval(value)),
+            Or(predicates) => predicates.iter().any(|p| p.eval(value)),
+            Not(predicate) => !predicate.eval(value),
+            Exists(path) => value.pointer(path).is_some(),
+            Eq(path, expected) => compare(value, path, expected) == Some(Ordering::Equal),
+            Ne(path, expected) => {
+                compare(value, path, expected).map_or(false, |ord| ord != Ordering::Equal)
+            }
+            Gt(path, expected) => compare(value, path, expected) == Some(Ordering::Greater),
+            Gte(path, expected) => {
+                compare(value, path, expected).map_or(false, |ord| ord != Ordering::Less)
+            }
+            Lt(path, expected) => compare(value, path, expected) == Some(Ordering::Less),
+            Lte(path, expected) => {
+                compare(value, path, expected).map_or(false, |ord| ord != Ordering::Greater)
+            }
+            In(path, expected) => expected
+                .iter()
+                .any(|expected| compare(value, path, expected) == Some(Ordering::Equal)),
+        }
+    }
+
+    /// The count of nodes of the predicate.
+    pub fn node_count(&self) -> usize {
+        use self::Predicate::*;
+        match self {
+            And(predicates) | Or(predicates) => {
+                1 + predicates.iter().map(Predicate::node_count).sum::<usize>()
+            }
+            Not(predicate) => 1 + predicate.node_count(),
+            In(_, values) => 1 + values.len(),
+            _ => 1,
+        }
+    }
+
+    /// Check that the paths are valid JSON pointers and the predicate isn't too complex.
+    pub fn check(&self) -> Result<(), String> {
+        use self::Predicate::*;
+        if self.node_count() > MAX_PREDICATE_NODES {
+            return Err(format!(
+                "Too complex predicate, max nodes: {}",
+                MAX_PREDICATE_NODES
+            ));
+        }
+        match self {
+            And(predicates) | Or(predicates) => predicates.iter().try_for_each(Predicate::check),
+            Not(predicate) => predicate.check(),
+            Exists(path)
+            | Eq(path, _)
+            | Ne(path, _)
+            | Gt(path, _)
+            | Gte(path, _)
+            | Lt(path, _)
+            | Lte(path, _)
+            | In(path, _) => {
+                if path.is_empty() || path.starts_with('/') {
+                    Ok(())
+                } else {
+                    Err(format!("Invalid JSON pointer: {:?}", path))
+                }
+            }
+        }
+    }
+}
+
+/// Compare the value at the path with the expected one, `None` if they are incomparable.
+fn compare(value: &Value, path: &str, expected: &Value) -> Option<Ordering> {
+    let actual = value.pointer(path)?;
+    match (actual, expected) {
+        (Value::Number(a), Value::Number(b)) => {
+            if let (Some(a), Some(b)) = (a.as_u64(), b.as_u64()) {
+                Some(a.cmp(&b))
+            } else if let (Some(a), Some(b)) = (a.as_i64(), b.as_i64()) {
+                Some(a.cmp(&b))
+            } else {
+                a.as_f64()?.partial_cmp(&b.as_f64()?)
+            }
+        }
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (a, b) if a == b => Some(Ordering::Equal),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_predicate() {
+        let value = json!({
+            "name": "Alice",
+            "is_active": false,
+            "total_nomination": 200_000,
+            "balance": {"Free": 100},
+        });
+        let predicate: Predicate = serde_json::from_value(json!({"and":[
+            {"eq":["/is_active", false]},
+            {"gt":["/total_nomination", 100_000]},
+            {"not":{"exists":"/jackpot"}},
+        ]}))
+        .unwrap();
+        assert!(predicate.eval(&value));
+        assert_eq!(predicate.node_count(), 4);
+        assert!(predicate.check().is_ok());
+
+        assert!(Predicate::Lte("/balance/Free".into(), json!(100)).eval(&value));
+        assert!(!Predicate::Lt("/balance/Free".into(), json!(100)).eval(&value));
+        assert!(Predicate::In("/name".into(), vec![json!("Bob"), json!("Alice")]).eval(&value));
+        assert!(!Predicate::Ne("/missing".into(), json!(1)).eval(&value));
+        assert!(!Predicate::Gt("/name".into(), json!(1)).eval(&value));
+        assert!(Predicate::Or(vec![
+            Predicate::Gte("/total_nomination".into(), json!(300_000)),
+            Predicate::Lt("/total_nomination".into(), json!(200_000.5)),
+        ])
+        .eval(&value));
+        assert!(Predicate::Exists("name".into()).check().is_err());
+    }
+}
@@ -1,81 +1,192 @@
+use std::collections::hash_map::RandomState;
 use std::collections::HashSet;
 use std::fmt::Debug;
+use std::hash::{BuildHasher, Hasher};
 use std::thread;
 use std::time::Duration;
 
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{json, Value};
 
-use super::util;
-use crate::Result;
+use super::{pattern, util, PrefixChange, RegisterContext, RegisterOptions, SubscriptionId};
+use crate::{Block, BlockMeta, Result};
 
 const MSG_CHUNK_SIZE_LIMIT: usize = 10;
 
 #[derive(PartialEq, Clone, Debug, Serialize)]
 pub struct Message {
+    /// The id of subscription that the message is produced for (absent for the replay).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    subscription: Option<SubscriptionId>,
+    /// The change of prefixes that takes effect from this message (or the earlier block height
+    /// whose message is empty).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prefix_change: Option<PrefixChange>,
     height: u64,
+    /// The metadata of block (hash, parent_hash, timestamp and author),
+    /// which is always carried regardless of the prefixes.
+    #[serde(flatten)]
+    meta: BlockMeta,
     data: Vec<Value>,
 }
 
 impl Message {
     /// Build a message with all json value that match the prefix successfully.
     /// The data of message may be empty, and empty message don't need to be pushed.
-    pub fn build(height: u64, values: &[Value], prefixes: &HashSet<String>) -> Self {
-        let data = values
+    pub fn build(
+        height: u64,
+        block: &Block,
+        prefixes: &HashSet<String>,
+        options: &RegisterOptions,
+    ) -> Self {
+        let data = block
+            .values
             .iter()
-            .cloned()
-            .filter(|value| {
-                let prefix = util::get_value_prefix(value);
-                prefixes.contains(&prefix)
+            .enumerate()
+            .filter_map(|(index, value)| {
+                let prefix = util::get_subscription_prefix(value);
+                if !pattern::matches(prefixes, &prefix) || !options.matches_entry(&prefix, value) {
+                    return None;
+                }
+                let mut value = value.clone();
+                if !options.accounts.is_empty() {
+                    let accounts = options.accounts.touched(&prefix, &value);
+                    if accounts.is_empty() {
+                        return None;
+                    }
+                    value["accounts"] = json!(accounts);
+                }
+                if options.raw {
+                    if let Some(raw) = block.raw_of(index) {
+                        value["raw"] = raw.to_json();
+                    }
+                }
+                Some(value)
             })
             .collect();
-        Self { height, data }
+        Self {
+            subscription: None,
+            prefix_change: None,
+            height,
+            meta: block.meta.clone(),
+            data,
+        }
     }
 
     pub fn empty(height: u64) -> Message {
         Message {
+            subscription: None,
+            prefix_change: None,
             height,
+            meta: BlockMeta::default(),
             data: vec![],
         }
     }
 
+    /// Mark the message with the id of subscription that it's produced for.
+    pub fn with_subscription(mut self, id: SubscriptionId) -> Self {
+        self.subscription = Some(id);
+        self
+    }
+
+    /// Attach the change of prefixes to the message.
+    pub fn with_prefix_change(mut self, change: Option<PrefixChange>) -> Self {
+        self.prefix_change = change;
+        self
+    }
+
+    pub fn prefix_change(&self) -> Option<&PrefixChange> {
+        self.prefix_change.as_ref()
+    }
+
+    /// The message that reports the change of prefixes is pushed even if its data is empty.
     pub fn is_empty(&self) -> bool {
-        self.data.is_empty()
+        self.data.is_empty() && self.prefix_change.is_none()
     }
 
-    /// Split the message into multiple messages according to `chunk_size`.
+    /// Split the message into multiple messages according to `chunk_size`,
+    /// only the first one carries the change of prefixes.
     pub fn split(self, chunk_size: usize) -> Vec<Self> {
+        if self.data.is_empty() {
+            return vec![self];
+        }
         self.data
             .chunks(chunk_size)
-            .map(|value| Message {
+            .enumerate()
+            .map(|(i, value)| Message {
+                subscription: self.subscription,
+                prefix_change: if i == 0 {
+                    self.prefix_change.clone()
+                } else {
+                    None
+                },
                 height: self.height,
+                meta: self.meta.clone(),
                 data: value.to_vec(),
             })
             .collect()
     }
 }
 
+/// The retry policy of push.
+///
+/// The failed message is retried with the exponential backoff (with jitter), the subscription
+/// is suspended (keeping its push height) after `retry_count` retries, and then probed by one
+/// attempt at a time until it's resumed, or dead-lettered after `dead_letter_after`.
 #[derive(Clone, Copy, Debug)]
 pub struct Config {
-    retry_count: u32,
-    retry_interval: Duration,
+    /// The count of retries before the subscription is suspended.
+    pub retry_count: u32,
+    /// The interval before the first retry, which is doubled for every retry.
+    pub initial_interval: Duration,
+    /// The max interval of retries (and probes).
+    pub max_interval: Duration,
+    /// The ratio of the random jitter of intervals, range: [0,1].
+    pub jitter: f64,
+    /// The duration of suspension after which the subscription is dropped (None means never).
+    pub dead_letter_after: Option<Duration>,
 }
 
-impl Config {
-    pub fn new(retry_count: u32, retry_interval: Duration) -> Self {
+impl Default for Config {
+    fn default() -> Self {
         Self {
-            retry_count,
-            retry_interval,
+            retry_count: 10,
+            initial_interval: Duration::from_secs(1),
+            max_interval: Duration::from_secs(60),
+            jitter: 0.2,
+            dead_letter_after: Some(Duration::from_secs(7 * 24 * 3600)),
         }
     }
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self::new(60, Duration::new(2, 0))
+impl Config {
+    /// The interval before the nth retry: `initial * 2^(n-1)`, capped at `max_interval`,
+    /// and then scaled by a random factor in [1-jitter, 1+jitter].
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exp = retry.saturating_sub(1).min(31);
+        let interval = self
+            .initial_interval
+            .checked_mul(1 << exp)
+            .map_or(self.max_interval, |interval| {
+                interval.min(self.max_interval)
+            });
+        let jitter = self.jitter.max(0.0).min(1.0);
+        interval.mul_f64(1.0 - jitter + 2.0 * jitter * random_ratio())
     }
 }
 
+/// A random number in [0,1), which is good enough for the jitter.
+fn random_ratio() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
 #[derive(Debug, Deserialize)]
 struct JsonResponse<T> {
     result: T,
@@ -86,6 +197,8 @@ pub struct PushClient {
     pub url: String,
     client: reqwest::blocking::Client,
     config: Config,
+    /// The context of subscription that the retry state is reported to.
+    context: Option<RegisterContext>,
 }
 
 impl PushClient {
@@ -98,6 +211,46 @@ impl PushClient {
             url,
             client: reqwest::blocking::Client::new(),
             config,
+            context: None,
+        }
+    }
+
+    /// Report the retry state and the last error to the context of subscription.
+    pub fn report_to(mut self, ctxt: RegisterContext) -> Self {
+        self.context = Some(ctxt);
+        self
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Report the successful post, return whether the subscription was suspended.
+    fn report_success(&self) -> bool {
+        match &self.context {
+            Some(ctxt) => {
+                let mut ctxt = ctxt.lock();
+                ctxt.retry_count = 0;
+                ctxt.suspended_since.take().is_some()
+            }
+            None => false,
+        }
+    }
+
+    /// Report the failed post, return the total count of retries of the subscription.
+    fn report_failure(&self, error: String) -> Option<u32> {
+        let ctxt = self.context.as_ref()?;
+        let mut ctxt = ctxt.lock();
+        ctxt.retry_count += 1;
+        ctxt.last_error = Some(error);
+        Some(ctxt.retry_count)
+    }
+
+    /// The count of attempts of a message, the suspended subscription is probed by one attempt.
+    fn attempts(&self) -> u32 {
+        match &self.context {
+            Some(ctxt) if ctxt.lock().suspended_since.is_some() => 1,
+            _ => self.config.retry_count.max(1),
         }
     }
 
@@ -119,18 +272,25 @@ impl PushClient {
     pub fn post_message(&self, msg: &Message) -> Result<()> {
         let body: Value = json!(msg);
         debug!("Send message request: {:?}", body);
-        for i in 1..=self.config.retry_count {
-            let ok = self.post::<String>(&body).unwrap_or_default();
-            if ok == "OK" {
-                info!("Post message successfully, height = {}", msg.height);
-                return Ok(());
+        let attempts = self.attempts();
+        for i in 1..=attempts {
+            let err = match self.post::<String>(&body) {
+                Ok(ok) if ok == "OK" => {
+                    info!("Post message successfully, height = {}", msg.height);
+                    if self.report_success() {
+                        info!("Subscription [{}] is resumed", &self.url);
+                    }
+                    return Ok(());
+                }
+                Ok(ok) => format!("Unexpected response: {:?}", ok),
+                Err(err) => err.to_string(),
+            };
+            warn!("Receive message response: {:?}", err);
+            let retry_count = self.report_failure(err).unwrap_or(i);
+            if i < attempts {
+                warn!("Send message request retry ({}/{})", i, attempts);
+                thread::sleep(self.config.backoff(retry_count));
             }
-            warn!("Receive message response: {:?}", ok);
-            warn!(
-                "Send message request retry ({}/{})",
-                i, self.config.retry_count
-            );
-            thread::sleep(self.config.retry_interval);
         }
         error!("Reach the limitation of retries");
         Err("Reach the limitation of retries".into())
@@ -154,6 +314,7 @@ impl PushClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::RawEntry;
 
     macro_rules! value {
         ($v:expr) => {
@@ -187,9 +348,17 @@ mod tests {
         ]"#
         );
         assert_eq!(
-            Message::build(0, &values0, &hash_set!("aaa".into(), "bbb".into())),
+            Message::build(
+                0,
+                &Block::new(values0.clone()),
+                &hash_set!("aaa".into(), "bbb".into()),
+                &RegisterOptions::default()
+            ),
             Message {
+                subscription: None,
+                prefix_change: None,
                 height: 0,
+                meta: BlockMeta::default(),
                 data: vec![
                     value!(r#"{"prefix":"aaa", "value":100}"#),
                     value!(r#"{"prefix":"bbb", "value":100}"#)
@@ -205,9 +374,17 @@ mod tests {
         ]"#
         );
         assert_eq!(
-            Message::build(1, &values1, &hash_set!("bbb".into(), "ccc".into())),
+            Message::build(
+                1,
+                &Block::new(values1.clone()),
+                &hash_set!("bbb".into(), "ccc".into()),
+                &RegisterOptions::default()
+            ),
             Message {
+                subscription: None,
+                prefix_change: None,
                 height: 1,
+                meta: BlockMeta::default(),
                 data: vec![
                     value!(r#"{"prefix":"bbb", "value":200}"#),
                     value!(r#"{"prefix":"ccc", "value":100}"#)
@@ -223,9 +400,17 @@ mod tests {
         ]"#
         );
         assert_eq!(
-            Message::build(2, &values2, &hash_set!("aaa".into(), "ccc".into())),
+            Message::build(
+                2,
+                &Block::new(values2.clone()),
+                &hash_set!("aaa".into(), "ccc".into()),
+                &RegisterOptions::default()
+            ),
             Message {
+                subscription: None,
+                prefix_change: None,
                 height: 2,
+                meta: BlockMeta::default(),
                 data: vec![
                     value!(r#"{"prefix":"aaa", "value":100}"#),
                     value!(r#"{"prefix":"ccc", "value":300}"#)
@@ -233,25 +418,253 @@ mod tests {
             }
         );
         assert_eq!(
-            Message::build(2, &values2, &hash_set!("aaa".into(), "ddd".into())),
+            Message::build(
+                2,
+                &Block::new(values2.clone()),
+                &hash_set!("aaa".into(), "ddd".into()),
+                &RegisterOptions::default()
+            ),
             Message {
+                subscription: None,
+                prefix_change: None,
                 height: 2,
+                meta: BlockMeta::default(),
                 data: vec![value!(r#"{"prefix":"aaa", "value":100}"#),]
             }
         );
         assert_eq!(
-            Message::build(2, &values2, &hash_set!("ddd".into())),
+            Message::build(
+                2,
+                &Block::new(values2.clone()),
+                &hash_set!("ddd".into()),
+                &RegisterOptions::default()
+            ),
             Message {
+                subscription: None,
+                prefix_change: None,
                 height: 2,
+                meta: BlockMeta::default(),
                 data: vec![]
             }
         );
     }
 
+    #[test]
+    fn test_message_build_passthrough() {
+        let values = values!(
+            r#"[
+            {"type":"map", "prefix":"aaa", "value":100},
+            {"type":"error", "prefix":"aaa", "key":"0x00", "value":"0x01"},
+            {"type":"unknown", "prefix":"bbb", "key":"0x02", "value":"0x03"}
+        ]"#
+        );
+        assert_eq!(
+            Message::build(
+                0,
+                &Block::new(values.clone()),
+                &hash_set!("aaa".into(), "bbb".into()),
+                &RegisterOptions::default()
+            ),
+            Message {
+                subscription: None,
+                prefix_change: None,
+                height: 0,
+                meta: BlockMeta::default(),
+                data: vec![value!(r#"{"type":"map", "prefix":"aaa", "value":100}"#)]
+            }
+        );
+        assert_eq!(
+            Message::build(
+                0,
+                &Block::new(values.clone()),
+                &hash_set!("$error".into(), "$unknown".into()),
+                &RegisterOptions::default()
+            ),
+            Message {
+                subscription: None,
+                prefix_change: None,
+                height: 0,
+                meta: BlockMeta::default(),
+                data: vec![
+                    value!(r#"{"type":"error", "prefix":"aaa", "key":"0x00", "value":"0x01"}"#),
+                    value!(r#"{"type":"unknown", "prefix":"bbb", "key":"0x02", "value":"0x03"}"#)
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn test_message_build_raw() {
+        let values = values!(
+            r#"[
+            {"prefix":"aaa", "value":100},
+            {"prefix":"aaa", "value":200}
+        ]"#
+        );
+        let block = Block::new(values.clone()).with_raw(vec![Some(RawEntry::new(b"aaa", &[100]))]);
+        assert_eq!(
+            Message::build(
+                0,
+                &block,
+                &hash_set!("aaa".into()),
+                &RegisterOptions::default()
+            )
+            .data,
+            values
+        );
+        let options = RegisterOptions {
+            raw: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            Message::build(0, &block, &hash_set!("aaa".into()), &options).data,
+            vec![
+                value!(
+                    r#"{"prefix":"aaa", "value":100, "raw":{"key":"0x616161", "value":"0x64"}}"#
+                ),
+                // the value without raw entry, e.g. the derived one
+                values[1].clone(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_message_build_filters() {
+        let values = values!(
+            r#"[
+            {"type":"map", "prefix":"aaa", "key":["0x01", "PCX"], "value":100},
+            {"type":"map", "prefix":"aaa", "key":["0x02", "PCX"], "value":200},
+            {"type":"map", "prefix":"bbb", "key":"0x02", "value":300}
+        ]"#
+        );
+        let options: RegisterOptions =
+            serde_json::from_str(r#"{"filters":{"aaa":{"component":0, "keys":["0x02"]}}}"#)
+                .unwrap();
+        assert_eq!(
+            Message::build(
+                0,
+                &Block::new(values.clone()),
+                &hash_set!("aaa".into(), "bbb".into()),
+                &options
+            )
+            .data,
+            vec![values[1].clone(), values[2].clone()]
+        );
+    }
+
+    #[test]
+    fn test_message_build_predicates() {
+        let values = values!(
+            r#"[
+            {"type":"map", "prefix":"aaa", "key":"0x01", "value":{"Free":100}},
+            {"type":"map", "prefix":"aaa", "key":"0x02", "value":{"Free":200}},
+            {"type":"map", "prefix":"bbb", "key":"0x03", "value":{"Free":100}}
+        ]"#
+        );
+        let options: RegisterOptions =
+            serde_json::from_str(r#"{"predicates":{"aaa":{"gt":["/Free", 150]}}}"#).unwrap();
+        assert_eq!(
+            Message::build(
+                0,
+                &Block::new(values.clone()),
+                &hash_set!("aaa".into(), "bbb".into()),
+                &options
+            )
+            .data,
+            vec![values[1].clone(), values[2].clone()]
+        );
+    }
+
+    #[test]
+    fn test_message_build_meta() {
+        let values = values!(
+            r#"[
+            {"prefix":"System ParentHash", "value":"0x01"},
+            {"prefix":"Timestamp Now", "value":1556432000},
+            {"prefix":"XSystem BlockProducer", "value":"0x02"},
+            {"prefix":"aaa", "value":100}
+        ]"#
+        );
+        let mut block = Block::new(values);
+        block.meta.hash = Some("0x03".into());
+        let msg = Message::build(
+            0,
+            &block,
+            &hash_set!("aaa".into()),
+            &RegisterOptions::default(),
+        )
+        .with_subscription(1);
+        assert_eq!(
+            json!(msg),
+            value!(
+                r#"{
+                "subscription":1,
+                "height":0,
+                "hash":"0x03",
+                "parent_hash":"0x01",
+                "timestamp":1556432000,
+                "author":"0x02",
+                "data":[{"prefix":"aaa", "value":100}]
+            }"#
+            )
+        );
+    }
+
+    #[test]
+    fn test_config_backoff() {
+        let config = Config {
+            jitter: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(config.backoff(1), Duration::from_secs(1));
+        assert_eq!(config.backoff(2), Duration::from_secs(2));
+        assert_eq!(config.backoff(6), Duration::from_secs(32));
+        assert_eq!(config.backoff(7), Duration::from_secs(60));
+        assert_eq!(config.backoff(1000), Duration::from_secs(60));
+
+        let config = Config::default();
+        for retry in 1..=10 {
+            let interval = config.backoff(retry).as_secs_f64();
+            let expected = 2f64.powi(retry as i32 - 1).min(60.0);
+            assert!(interval >= expected * 0.8 && interval <= expected * 1.2);
+        }
+    }
+
+    #[test]
+    fn test_client_report() {
+        use super::super::Context;
+        use parking_lot::Mutex;
+        use std::sync::Arc;
+
+        let ctxt = Arc::new(Mutex::new(Context::new(
+            "http://127.0.0.1:12345".into(),
+            vec!["Timestamp Now".into()],
+            semver::Version::new(1, 0, 0),
+            RegisterOptions::default(),
+        )));
+        let client = PushClient::new("http://127.0.0.1:12345".into()).report_to(ctxt.clone());
+        assert_eq!(client.attempts(), 10);
+        assert_eq!(client.report_failure("error".into()), Some(1));
+        assert_eq!(client.report_failure("error".into()), Some(2));
+        assert_eq!(ctxt.lock().last_error, Some("error".into()));
+
+        // the suspended subscription is probed by one attempt, and resumed by the success
+        ctxt.lock().suspended_since = Some(1000);
+        assert_eq!(client.attempts(), 1);
+        assert!(client.report_success());
+        assert_eq!(ctxt.lock().suspended_since, None);
+        assert_eq!(ctxt.lock().retry_count, 0);
+        assert_eq!(client.attempts(), 10);
+        assert!(!client.report_success());
+    }
+
     #[test]
     fn test_message_split() {
         let message = Message {
+            subscription: Some(1),
+            prefix_change: None,
             height: 123,
+            meta: BlockMeta::default(),
             data: vec![
                 value!("1"),
                 value!("2"),
@@ -264,15 +677,24 @@ mod tests {
         assert_eq!(
             vec![
                 Message {
+                    subscription: Some(1),
+                    prefix_change: None,
                     height: 123,
+                    meta: BlockMeta::default(),
                     data: vec![value!("1"), value!("2")]
                 },
                 Message {
+                    subscription: Some(1),
+                    prefix_change: None,
                     height: 123,
+                    meta: BlockMeta::default(),
                     data: vec![value!("3"), value!("4")]
                 },
                 Message {
+                    subscription: Some(1),
+                    prefix_change: None,
                     height: 123,
+                    meta: BlockMeta::default(),
                     data: vec![value!("5")]
                 },
             ],
@@ -281,4 +703,28 @@ mod tests {
 
         assert_eq!(vec![message.clone()], message.split(5));
     }
+
+    #[test]
+    fn test_message_split_prefix_change() {
+        let change = PrefixChange {
+            height: 123,
+            prefixes: vec!["aaa".into()],
+            version: "1.0.0".into(),
+        };
+        let message = Message::empty(123)
+            .with_subscription(1)
+            .with_prefix_change(Some(change.clone()));
+        // the empty message that reports the change is still pushed
+        assert!(!message.is_empty());
+        assert_eq!(vec![message.clone()], message.clone().split(2));
+
+        let message = Message {
+            data: vec![value!("1"), value!("2"), value!("3")],
+            ..message
+        };
+        let messages = message.split(2);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].prefix_change(), Some(&change));
+        assert_eq!(messages[1].prefix_change(), None);
+    }
 }
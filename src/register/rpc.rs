@@ -1,65 +1,324 @@
-use std::collections::hash_map::Entry;
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use jsonrpc_derive::rpc;
 use parking_lot::Mutex;
 use semver::Version;
 
-use super::{Context, RegisterService};
-use crate::Result;
+use super::status::SubscriptionInfo;
+use super::{
+    max_queued_height, pattern, Context, NotifyData, PrefixChange, RegisterOptions,
+    RegisterService, SubscriptionId,
+};
+use crate::parse::{catalog, CatalogEntry};
+use crate::store::{StoreApi, StoreRpc};
+use crate::{Result, SharedStateStore};
 
 /// Register API
 #[rpc(server)]
 pub trait RegisterApi {
-    /// Register
+    /// Register a new subscription, return the id of subscription
     #[rpc(name = "register")]
-    fn register(&self, prefixes: Vec<String>, url: String, version: String) -> Result<String>;
+    fn register(
+        &self,
+        prefixes: Vec<String>,
+        url: String,
+        version: String,
+        options: Option<RegisterOptions>,
+    ) -> Result<SubscriptionId>;
 
-    /// Deregister
+    /// Update the prefixes (and the options) of the subscription
+    #[rpc(name = "update")]
+    fn update(
+        &self,
+        id: SubscriptionId,
+        prefixes: Vec<String>,
+        version: String,
+        options: Option<RegisterOptions>,
+    ) -> Result<String>;
+
+    /// Add the prefixes to the subscription, return the change with its effective height
+    #[rpc(name = "addPrefixes")]
+    fn add_prefixes(&self, id: SubscriptionId, prefixes: Vec<String>) -> Result<PrefixChange>;
+
+    /// Remove the prefixes from the subscription, return the change with its effective height
+    #[rpc(name = "removePrefixes")]
+    fn remove_prefixes(&self, id: SubscriptionId, prefixes: Vec<String>) -> Result<PrefixChange>;
+
+    /// Replace all prefixes (and the version) of the subscription,
+    /// return the change with its effective height
+    #[rpc(name = "setPrefixes")]
+    fn set_prefixes(
+        &self,
+        id: SubscriptionId,
+        prefixes: Vec<String>,
+        version: Option<String>,
+    ) -> Result<PrefixChange>;
+
+    /// Deregister the subscription
     #[rpc(name = "deregister")]
-    fn deregister(&self, url: String) -> Result<String>;
+    fn deregister(&self, id: SubscriptionId) -> Result<String>;
+
+    /// List the status of all subscriptions (including the recently ended ones)
+    #[rpc(name = "listSubscriptions")]
+    fn list_subscriptions(&self) -> Result<Vec<SubscriptionInfo>>;
+
+    /// Get the status of the subscription
+    #[rpc(name = "getSubscription")]
+    fn get_subscription(&self, id: SubscriptionId) -> Result<SubscriptionInfo>;
+
+    /// Pause pushing the blocks to the subscription
+    #[rpc(name = "pauseSubscription")]
+    fn pause_subscription(&self, id: SubscriptionId) -> Result<String>;
+
+    /// Resume pushing the blocks to the subscription
+    #[rpc(name = "resumeSubscription")]
+    fn resume_subscription(&self, id: SubscriptionId) -> Result<String>;
+
+    /// Reset the next push height of the subscription
+    #[rpc(name = "resetSubscription")]
+    fn reset_subscription(&self, id: SubscriptionId, height: u64) -> Result<String>;
+
+    /// Replay the archived blocks in the block height range [from, to] to the url
+    #[rpc(name = "replay")]
+    fn replay(
+        &self,
+        prefixes: Vec<String>,
+        url: String,
+        from: u64,
+        to: u64,
+        options: Option<RegisterOptions>,
+    ) -> Result<String>;
+
+    /// Get the catalog of runtime storages, with the JSON Schemas of key and value
+    #[rpc(name = "getCatalog")]
+    fn get_catalog(&self) -> Result<Vec<CatalogEntry>>;
 }
 
 impl RegisterApi for RegisterService {
-    fn register(&self, prefixes: Vec<String>, url: String, version: String) -> Result<String> {
+    fn register(
+        &self,
+        prefixes: Vec<String>,
+        url: String,
+        version: String,
+        options: Option<RegisterOptions>,
+    ) -> Result<SubscriptionId> {
         let register_info = format!(
-            "url: {:?}, prefix: {:?}, version: {:?}",
-            &url, &prefixes, &version
+            "url: {:?}, prefix: {:?}, version: {:?}, options: {:?}",
+            &url, &prefixes, &version, &options
+        );
+        let version = Version::parse(&version)?;
+        pattern::check_patterns(&prefixes)?;
+        let options = options.unwrap_or_default();
+        options.check_filters(&prefixes.iter().cloned().collect())?;
+        self.check_heights(&options)?;
+        // taken at once, so that the unavailable snapshot is rejected here
+        // and the snapshot won't be missed after the state store moves on
+        let snapshot = match options.snapshot_height {
+            Some(height) => Some(self.take_snapshot(height)?),
+            None => None,
+        };
+        let id = self.next_subscription_id();
+        info!("New Register [id: {}, {}]", id, register_info);
+        let tx = self.tx.lock().clone();
+        let ctxt = Arc::new(Mutex::new(Context::new(url, prefixes, version, options)));
+        // inserted before the push thread starts, which may end (and remove it) at once
+        self.map.write().insert(id, ctxt.clone());
+        self.spawn_new_push(id, ctxt, snapshot, tx);
+        self.save_registrations();
+        Ok(id)
+    }
+
+    fn update(
+        &self,
+        id: SubscriptionId,
+        prefixes: Vec<String>,
+        version: String,
+        options: Option<RegisterOptions>,
+    ) -> Result<String> {
+        info!(
+            "Update Register [id: {}, prefix: {:?}, version: {:?}, options: {:?}]",
+            id, &prefixes, &version, &options
         );
         let version = Version::parse(&version)?;
-        match self.map.write().entry(url.clone()) {
-            Entry::Occupied(mut entry) => {
-                info!("Existing Register [{}]", register_info);
-                let ctxt = entry.get_mut();
-                ctxt.lock().update_prefixes(prefixes, version);
+        pattern::check_patterns(&prefixes)?;
+        if let Some(options) = &options {
+            self.check_heights(options)?;
+        }
+        {
+            let map = self.map.read();
+            let mut ctxt = map.get(&id).ok_or("Nonexistent subscription id")?.lock();
+            if let Some(options) = &options {
+                // the prefixes are extended unless the version is newer
+                let mut patterns = prefixes.iter().cloned().collect::<HashSet<_>>();
+                if version <= ctxt.version {
+                    patterns.extend(ctxt.prefixes.iter().cloned());
+                }
+                options.check_filters(&patterns)?;
+            }
+            ctxt.update_prefixes(prefixes, version);
+            if let Some(options) = options {
+                ctxt.options = options;
             }
-            Entry::Vacant(entry) => {
-                info!("New Register [{}]", register_info);
-                let tx = self.tx.lock().clone();
-                let ctxt = Arc::new(Mutex::new(Context::new(prefixes, version)));
-                self.spawn_new_push(url, ctxt.clone(), tx);
-                entry.insert(ctxt);
+        }
+        self.save_registrations();
+        Ok("OK".to_string())
+    }
+
+    fn add_prefixes(&self, id: SubscriptionId, prefixes: Vec<String>) -> Result<PrefixChange> {
+        info!("Add prefixes [id: {}, prefix: {:?}]", id, &prefixes);
+        pattern::check_patterns(&prefixes)?;
+        self.change_prefixes(id, |ctxt| {
+            let mut added = ctxt.prefixes.clone();
+            added.extend(prefixes);
+            let version = ctxt.version.clone();
+            Ok(ctxt.set_prefixes(added, version))
+        })
+    }
+
+    fn remove_prefixes(&self, id: SubscriptionId, prefixes: Vec<String>) -> Result<PrefixChange> {
+        info!("Remove prefixes [id: {}, prefix: {:?}]", id, &prefixes);
+        self.change_prefixes(id, |ctxt| {
+            let mut removed = ctxt.prefixes.clone();
+            for prefix in &prefixes {
+                if !removed.remove(prefix) {
+                    return Err(format!("Unsubscribed prefix: {:?}", prefix).into());
+                }
             }
+            let version = ctxt.version.clone();
+            Ok(ctxt.set_prefixes(removed, version))
+        })
+    }
+
+    fn set_prefixes(
+        &self,
+        id: SubscriptionId,
+        prefixes: Vec<String>,
+        version: Option<String>,
+    ) -> Result<PrefixChange> {
+        info!(
+            "Set prefixes [id: {}, prefix: {:?}, version: {:?}]",
+            id, &prefixes, &version
+        );
+        let version = version
+            .map(|version| Version::parse(&version))
+            .transpose()?;
+        pattern::check_patterns(&prefixes)?;
+        self.change_prefixes(id, |ctxt| {
+            let version = match version {
+                Some(version) if version < ctxt.version => {
+                    return Err("The version is lower than the current one".into())
+                }
+                Some(version) => version,
+                None => ctxt.version.clone(),
+            };
+            Ok(ctxt.set_prefixes(prefixes.into_iter().collect(), version))
+        })
+    }
+
+    fn deregister(&self, id: SubscriptionId) -> Result<String> {
+        let map = self.map.read();
+        let ctxt = map.get(&id).ok_or("Nonexistent subscription id")?;
+        info!("Deregister #{}", id);
+        ctxt.lock().deregister = true;
+        self.block_queue.notify();
+        Ok("OK".to_string())
+    }
+
+    fn list_subscriptions(&self) -> Result<Vec<SubscriptionInfo>> {
+        let max_block_height = max_queued_height(&self.block_queue);
+        let mut subscriptions = self
+            .map
+            .read()
+            .iter()
+            .map(|(id, ctxt)| SubscriptionInfo::new(*id, &ctxt.lock(), max_block_height))
+            .chain(self.ended.read().values().cloned())
+            .collect::<Vec<_>>();
+        subscriptions.sort_by_key(|info| info.id);
+        Ok(subscriptions)
+    }
+
+    fn get_subscription(&self, id: SubscriptionId) -> Result<SubscriptionInfo> {
+        if let Some(ctxt) = self.map.read().get(&id) {
+            let max_block_height = max_queued_height(&self.block_queue);
+            return Ok(SubscriptionInfo::new(id, &ctxt.lock(), max_block_height));
         }
+        match self.ended.read().get(&id) {
+            Some(info) => Ok(info.clone()),
+            None => Err("Nonexistent subscription id".into()),
+        }
+    }
+
+    fn pause_subscription(&self, id: SubscriptionId) -> Result<String> {
+        info!("Pause subscription #{}", id);
+        self.set_paused(id, true)?;
         Ok("OK".to_string())
     }
 
-    fn deregister(&self, url: String) -> Result<String> {
-        match self.map.write().entry(url.clone()) {
-            Entry::Occupied(mut entry) => {
-                info!("Deregister [{}]", url);
-                let ctxt = entry.get_mut();
-                ctxt.lock().deregister = true;
-                Ok("OK".to_string())
+    fn resume_subscription(&self, id: SubscriptionId) -> Result<String> {
+        info!("Resume subscription #{}", id);
+        self.set_paused(id, false)?;
+        Ok("OK".to_string())
+    }
+
+    fn reset_subscription(&self, id: SubscriptionId, height: u64) -> Result<String> {
+        info!("Reset subscription #{} to block #{}", id, height);
+        self.check_block_available(height)?;
+        {
+            let map = self.map.read();
+            let mut ctxt = map.get(&id).ok_or("Nonexistent subscription id")?.lock();
+            if ctxt.options.to_height.map_or(false, |to| height > to) {
+                return Err("The height is beyond the to_height of subscription".into());
             }
-            Entry::Vacant(_) => Err("Nonexistent register url".into()),
+            ctxt.reset(height);
+            // under the lock of context, so that it isn't overtaken by the ack of push
+            self.tx
+                .lock()
+                .send(NotifyData::Reset((id, height)))
+                .expect("Unable to send context");
+        }
+        self.block_queue.notify();
+        self.save_registrations();
+        Ok("OK".to_string())
+    }
+
+    fn replay(
+        &self,
+        prefixes: Vec<String>,
+        url: String,
+        from: u64,
+        to: u64,
+        options: Option<RegisterOptions>,
+    ) -> Result<String> {
+        info!(
+            "Replay [url: {:?}, prefix: {:?}, range: [{}, {}], options: {:?}]",
+            &url, &prefixes, from, to, &options
+        );
+        if from > to {
+            return Err("Invalid block height range".into());
         }
+        pattern::check_patterns(&prefixes)?;
+        let prefixes = prefixes.into_iter().collect();
+        let options = options.unwrap_or_default();
+        options.check_filters(&prefixes)?;
+        self.spawn_replay(url, (from, to), prefixes, options)?;
+        Ok("OK".to_string())
+    }
+
+    fn get_catalog(&self) -> Result<Vec<CatalogEntry>> {
+        Ok(catalog())
     }
 }
 
-pub fn rpc_handler<R: RegisterApi>(register: R) -> jsonrpc_core::IoHandler {
+pub fn rpc_handler<R: RegisterApi>(
+    register: R,
+    store: Option<SharedStateStore>,
+) -> jsonrpc_core::IoHandler {
     let mut io = jsonrpc_core::IoHandler::default();
     io.extend_with(register.to_delegate());
+    if let Some(store) = store {
+        io.extend_with(StoreRpc::new(store).to_delegate());
+    }
     io
 }
 
@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use serde::Serialize;
+
+use super::{Context, RegisterOptions, SubscriptionId};
+
+/// The max count of the ended subscriptions that are kept for introspection.
+const MAX_ENDED_SUBSCRIPTIONS: usize = 100;
+
+/// The ended subscriptions (deregistered, dropped or completed), which are kept so that
+/// the registrant can find out why the subscription has gone.
+pub type EndedMap = Arc<RwLock<BTreeMap<SubscriptionId, SubscriptionInfo>>>;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SubscriptionState {
+    /// The blocks are being pushed.
+    Active,
+    /// The push of message is being retried.
+    Retrying,
+    /// The push kept failing, the subscription is probed until the registrant comes back.
+    Suspended,
+    /// The push is paused by `pauseSubscription`.
+    Paused,
+    /// The subscription has been deregistered.
+    Deregistered,
+    /// The subscription has been dropped since the push failed.
+    Dropped,
+    /// The subscription has been dropped since it was suspended for too long.
+    DeadLettered,
+    /// The last block height (`to_height`) has been pushed.
+    Completed,
+}
+
+/// The status of subscription, which is returned by `listSubscriptions` and `getSubscription`.
+#[derive(PartialEq, Clone, Debug, Serialize)]
+pub struct SubscriptionInfo {
+    pub id: SubscriptionId,
+    pub url: String,
+    pub prefixes: Vec<String>,
+    pub version: String,
+    pub options: RegisterOptions,
+    pub state: SubscriptionState,
+    /// The next block height to push.
+    pub push_height: u64,
+    /// The count of blocks between the next push height and the latest block.
+    pub lag: u64,
+    /// The count of retries of the current message.
+    pub retry_count: u32,
+    /// The unix timestamp when the subscription was suspended.
+    pub suspended_since: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+impl SubscriptionInfo {
+    pub fn new(id: SubscriptionId, ctxt: &Context, max_block_height: u64) -> Self {
+        let mut prefixes = ctxt.prefixes.iter().cloned().collect::<Vec<_>>();
+        prefixes.sort();
+        let state = if ctxt.deregister {
+            SubscriptionState::Deregistered
+        } else if ctxt.paused {
+            SubscriptionState::Paused
+        } else if ctxt.suspended_since.is_some() {
+            SubscriptionState::Suspended
+        } else if ctxt.retry_count > 0 {
+            SubscriptionState::Retrying
+        } else {
+            SubscriptionState::Active
+        };
+        Self {
+            id,
+            url: ctxt.url.clone(),
+            prefixes,
+            version: ctxt.version.to_string(),
+            options: ctxt.options.clone(),
+            state,
+            push_height: ctxt.push_height,
+            lag: max_block_height.saturating_sub(ctxt.push_height),
+            retry_count: ctxt.retry_count,
+            suspended_since: ctxt.suspended_since,
+            last_error: ctxt.last_error.clone(),
+        }
+    }
+}
+
+/// Keep the info of the ended subscription, the oldest ones are discarded.
+pub fn record_ended(ended: &EndedMap, mut info: SubscriptionInfo, state: SubscriptionState) {
+    info.state = state;
+    let mut ended = ended.write();
+    ended.insert(info.id, info);
+    while ended.len() > MAX_ENDED_SUBSCRIPTIONS {
+        let oldest = *ended.keys().next().expect("ended is not empty");
+        ended.remove(&oldest);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use semver::Version;
+
+    fn info(id: SubscriptionId, ctxt: &Context) -> SubscriptionInfo {
+        SubscriptionInfo::new(id, ctxt, 20)
+    }
+
+    #[test]
+    fn test_subscription_state() {
+        let mut ctxt = Context::new(
+            "http://127.0.0.1:12345".into(),
+            vec!["Timestamp Now".into()],
+            Version::new(1, 0, 0),
+            RegisterOptions::default(),
+        );
+        ctxt.reset(15);
+        assert_eq!(info(1, &ctxt).state, SubscriptionState::Active);
+        assert_eq!(info(1, &ctxt).lag, 5);
+
+        ctxt.retry_count = 1;
+        assert_eq!(info(1, &ctxt).state, SubscriptionState::Retrying);
+        ctxt.suspended_since = Some(1_500_000_000);
+        assert_eq!(info(1, &ctxt).state, SubscriptionState::Suspended);
+        ctxt.paused = true;
+        assert_eq!(info(1, &ctxt).state, SubscriptionState::Paused);
+        ctxt.deregister = true;
+        assert_eq!(info(1, &ctxt).state, SubscriptionState::Deregistered);
+
+        // the push height is ahead of the latest block
+        ctxt.reset(30);
+        assert_eq!(info(1, &ctxt).lag, 0);
+    }
+
+    #[test]
+    fn test_record_ended() {
+        let ctxt = Context::new(
+            "http://127.0.0.1:12345".into(),
+            vec!["Timestamp Now".into()],
+            Version::new(1, 0, 0),
+            RegisterOptions::default(),
+        );
+        let ended = EndedMap::default();
+        for id in 1..=MAX_ENDED_SUBSCRIPTIONS as SubscriptionId + 2 {
+            record_ended(&ended, info(id, &ctxt), SubscriptionState::Completed);
+        }
+        let ended = ended.read();
+        assert_eq!(ended.len(), MAX_ENDED_SUBSCRIPTIONS);
+        // the oldest ones are discarded
+        assert_eq!(ended.keys().next(), Some(&3));
+        assert!(ended
+            .values()
+            .all(|info| info.state == SubscriptionState::Completed));
+    }
+}
@@ -1,3 +1,6 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::parse::{ERROR_PREFIX, UNKNOWN_PREFIX};
 use crate::BlockQueue;
 
 pub fn get_value_prefix(value: &serde_json::Value) -> String {
@@ -5,6 +8,18 @@ pub fn get_value_prefix(value: &serde_json::Value) -> String {
     serde_json::from_value(value["prefix"].clone()).unwrap()
 }
 
+/// Get the prefix that used to match the subscription of the value.
+///
+/// The storage entries that can't be parsed are only matched by the pseudo prefixes,
+/// which means that registrant must subscribe them explicitly.
+pub fn get_subscription_prefix(value: &serde_json::Value) -> String {
+    match value["type"].as_str() {
+        Some("unknown") => UNKNOWN_PREFIX.to_string(),
+        Some("error") => ERROR_PREFIX.to_string(),
+        _ => get_value_prefix(value),
+    }
+}
+
 /// Get the max key of BTreeMap, which is max block height of block queue.
 #[inline]
 pub fn get_max_block_height(queue: &BlockQueue) -> u64 {
@@ -21,3 +36,11 @@ pub fn get_min_block_height(queue: &BlockQueue) -> u64 {
 pub fn get_block_queue_len(queue: &BlockQueue) -> usize {
     queue.read().len()
 }
+
+/// The current unix timestamp, unit: SECOND.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use byteorder::{ByteOrder, LittleEndian};
+use flate2::{read::DeflateDecoder, write::DeflateEncoder, Compression};
+
+use crate::{Block, Result};
+
+/// The max size of a segment file, unit: byte.
+const SEGMENT_SIZE_LIMIT: u64 = 64 * 1024 * 1024;
+/// The size of index entry: height (u64) + segment (u32) + offset (u64) + length (u32).
+const INDEX_ENTRY_SIZE: usize = 24;
+const INDEX_FILE_NAME: &str = "index";
+
+/// The location of the archived block.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+struct Location {
+    segment: u32,
+    offset: u64,
+    len: u32,
+}
+
+/// The on-disk archive of the parsed blocks.
+///
+/// The blocks are compressed (deflate) and appended to the segment files (`segment-<id>`),
+/// and located by the height index (`index`), which is a sequence of fixed-size entries.
+/// When a block is archived again (the sync fallback), the latest one takes effect.
+pub struct BlockArchive {
+    dir: PathBuf,
+    index_file: File,
+    /// block height => location of the block.
+    index: BTreeMap<u64, Location>,
+    /// The id, file and length of the current segment.
+    segment: (u32, File, u64),
+}
+
+impl BlockArchive {
+    /// Open the archive in the directory (created if it doesn't exist) and load the height index.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        let mut index_file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(dir.join(INDEX_FILE_NAME))?;
+
+        let mut buf = vec![];
+        index_file.read_to_end(&mut buf)?;
+        // the last entry may be incomplete (interrupted writing)
+        let complete = buf.len() - buf.len() % INDEX_ENTRY_SIZE;
+        if complete != buf.len() {
+            warn!("Truncate the incomplete block archive index entry");
+            index_file.set_len(complete as u64)?;
+        }
+        let index = buf[..complete]
+            .chunks(INDEX_ENTRY_SIZE)
+            .map(|entry| {
+                let location = Location {
+                    segment: LittleEndian::read_u32(&entry[8..12]),
+                    offset: LittleEndian::read_u64(&entry[12..20]),
+                    len: LittleEndian::read_u32(&entry[20..24]),
+                };
+                (LittleEndian::read_u64(&entry[0..8]), location)
+            })
+            .collect::<BTreeMap<_, _>>();
+
+        let id = index.values().map(|loc| loc.segment).max().unwrap_or(0);
+        let (file, len) = Self::open_segment(&dir, id)?;
+        info!(
+            "Open block archive {:?}, blocks: {}, segment: {}",
+            dir,
+            index.len(),
+            id
+        );
+        Ok(Self {
+            dir,
+            index_file,
+            index,
+            segment: (id, file, len),
+        })
+    }
+
+    fn segment_path(dir: &Path, id: u32) -> PathBuf {
+        dir.join(format!("segment-{:06}", id))
+    }
+
+    fn open_segment(dir: &Path, id: u32) -> Result<(File, u64)> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Self::segment_path(dir, id))?;
+        let len = file.metadata()?.len();
+        Ok((file, len))
+    }
+
+    /// The range of archived block heights, [min, max].
+    pub fn heights(&self) -> Option<(u64, u64)> {
+        let min = self.index.keys().next()?;
+        let max = self.index.keys().next_back()?;
+        Some((*min, *max))
+    }
+
+    /// Append the block to the archive.
+    pub fn append(&mut self, height: u64, block: &Block) -> Result<()> {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        serde_json::to_writer(&mut encoder, block)?;
+        let data = encoder.finish()?;
+
+        if self.segment.2 != 0 && self.segment.2 + data.len() as u64 > SEGMENT_SIZE_LIMIT {
+            let id = self.segment.0 + 1;
+            let (file, len) = Self::open_segment(&self.dir, id)?;
+            info!("Roll block archive to segment: {}", id);
+            self.segment = (id, file, len);
+        }
+        let (segment, file, offset) = &mut self.segment;
+        file.write_all(&data)?;
+        file.flush()?;
+        let location = Location {
+            segment: *segment,
+            offset: *offset,
+            len: data.len() as u32,
+        };
+        *offset += data.len() as u64;
+
+        let mut entry = [0u8; INDEX_ENTRY_SIZE];
+        LittleEndian::write_u64(&mut entry[0..8], height);
+        LittleEndian::write_u32(&mut entry[8..12], location.segment);
+        LittleEndian::write_u64(&mut entry[12..20], location.offset);
+        LittleEndian::write_u32(&mut entry[20..24], location.len);
+        self.index_file.write_all(&entry)?;
+        self.index_file.flush()?;
+        self.index.insert(height, location);
+        Ok(())
+    }
+
+    fn read(&self, height: u64) -> Result<Option<Block>> {
+        let location = match self.index.get(&height) {
+            Some(location) => location,
+            None => return Ok(None),
+        };
+        let mut file = File::open(Self::segment_path(&self.dir, location.segment))?;
+        file.seek(SeekFrom::Start(location.offset))?;
+        let decoder = DeflateDecoder::new(file.take(u64::from(location.len)));
+        Ok(Some(serde_json::from_reader(decoder)?))
+    }
+
+    /// Get the archived block, the hash of block is filled by the parent hash of the next block.
+    pub fn get(&self, height: u64) -> Result<Option<Block>> {
+        let mut block = match self.read(height)? {
+            Some(block) => block,
+            None => return Ok(None),
+        };
+        if let Some(next) = self.read(height.saturating_add(1))? {
+            block.meta.hash = next.meta.parent_hash;
+        }
+        Ok(Some(block))
+    }
+
+    /// Get the archived blocks in the block height range [from, to].
+    pub fn range(&self, from: u64, to: u64) -> Result<Vec<(u64, Block)>> {
+        let mut blocks: Vec<(u64, Block)> = vec![];
+        for &height in self
+            .index
+            .range(from..=to.saturating_add(1))
+            .map(|(h, _)| h)
+        {
+            let block = match self.read(height)? {
+                Some(block) => block,
+                None => continue,
+            };
+            if let Some((prev, prev_block)) = blocks.last_mut() {
+                if *prev + 1 == height {
+                    prev_block.meta.hash = block.meta.parent_hash.clone();
+                }
+            }
+            if height > to {
+                break;
+            }
+            blocks.push((height, block));
+        }
+        Ok(blocks)
+    }
+
+    /// Write the archived blocks in the block height range [from, to] as JSON lines,
+    /// in the format of pushed message (all entries of block are included).
+    pub fn replay<W: Write>(&self, from: u64, to: u64, mut out: W) -> Result<usize> {
+        let mut count = 0;
+        // read the blocks in batches, rather than the whole range at once.
+        let mut start = from;
+        while start <= to {
+            let end = to.min(start.saturating_add(999));
+            for (height, block) in self.range(start, end)? {
+                let mut msg = serde_json::to_value(&block.meta)?;
+                msg["height"] = height.into();
+                msg["data"] = block.values.into();
+                serde_json::to_writer(&mut out, &msg)?;
+                out.write_all(b"\n")?;
+                count += 1;
+            }
+            if end == to {
+                break;
+            }
+            start = end + 1;
+        }
+        out.flush()?;
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn block(parent_hash: &str, timestamp: u64) -> Block {
+        Block::new(vec![
+            json!({"type":"value", "prefix":"System ParentHash", "key":null, "value":parent_hash}),
+            json!({"type":"value", "prefix":"Timestamp Now", "key":null, "value":timestamp}),
+        ])
+    }
+
+    #[test]
+    fn test_block_archive() {
+        let dir = std::env::temp_dir().join(format!("block-archive-{}", std::process::id()));
+        let mut archive = BlockArchive::open(&dir).unwrap();
+        archive.append(0, &block("0x00", 1000)).unwrap();
+        archive.append(1, &block("0x01", 1001)).unwrap();
+        archive.append(2, &block("0x0f", 1002)).unwrap();
+        // sync fallback
+        archive.append(2, &block("0x02", 1002)).unwrap();
+        archive.append(3, &block("0x03", 1003)).unwrap();
+
+        let archive = BlockArchive::open(&dir).unwrap();
+        assert_eq!(archive.heights(), Some((0, 3)));
+        let block = archive.get(1).unwrap().unwrap();
+        assert_eq!(block.meta.timestamp, Some(1001));
+        assert_eq!(block.meta.hash, Some("0x02".into()));
+        assert_eq!(archive.get(4).unwrap(), None);
+
+        let blocks = archive.range(1, 2).unwrap();
+        assert_eq!(blocks.len(), 2);
+        assert_eq!(blocks[1].0, 2);
+        assert_eq!(blocks[1].1.meta.hash, Some("0x03".into()));
+        assert_eq!(archive.range(3, 10).unwrap()[0].1.meta.hash, None);
+
+        let mut out = vec![];
+        assert_eq!(archive.replay(0, 3, &mut out).unwrap(), 4);
+        let lines = String::from_utf8(out).unwrap();
+        let msg: serde_json::Value = serde_json::from_str(lines.lines().next().unwrap()).unwrap();
+        assert_eq!(msg["height"], 0);
+        assert_eq!(msg["hash"], "0x01");
+        assert_eq!(msg["data"].as_array().unwrap().len(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
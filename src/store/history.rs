@@ -0,0 +1,323 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{StorageEntry, StorageHistory};
+use crate::Result;
+
+/// The record of a storage change, which is a line of the history file.
+#[derive(Serialize, Deserialize)]
+struct Record {
+    height: u64,
+    prefix: String,
+    key: Value,
+    value: Value,
+}
+
+/// The versioned history of every storage key, which is persisted to an append-only JSONL file,
+/// and indexed in memory by the change heights and the file offsets of the records.
+///
+/// The records are appended in the order of block height, when a block is applied again
+/// (the sync fallback), the records of that block and the later blocks are truncated.
+pub struct HistoryStore {
+    path: PathBuf,
+    file: File,
+    /// The length of the history file.
+    len: u64,
+    /// block height => the file offset of the first record of the block.
+    blocks: BTreeMap<u64, u64>,
+    /// prefix => (key (json string) => [(change height, file offset)])
+    index: BTreeMap<String, BTreeMap<String, Vec<(u64, u64)>>>,
+}
+
+impl HistoryStore {
+    /// Open the history file (created if it doesn't exist) and rebuild the index.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)?;
+
+        let mut history = Self {
+            path,
+            file,
+            len: 0,
+            blocks: BTreeMap::new(),
+            index: BTreeMap::new(),
+        };
+        let mut reader = BufReader::new(File::open(&history.path)?);
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let size = reader.read_line(&mut line)? as u64;
+            if size == 0 {
+                break;
+            }
+            if !line.ends_with('\n') {
+                // the last record is incomplete (interrupted writing)
+                warn!("Truncate the incomplete history record: {}", line);
+                break;
+            }
+            let record: Record = serde_json::from_str(&line)?;
+            history.index_record(&record, history.len);
+            history.len += size;
+        }
+        history.file.set_len(history.len)?;
+        info!(
+            "Load storage history from {:?}, blocks: {}",
+            history.path,
+            history.blocks.len()
+        );
+        Ok(history)
+    }
+
+    /// The height of the latest applied block.
+    pub fn height(&self) -> Option<u64> {
+        self.blocks.keys().next_back().copied()
+    }
+
+    /// Append the storage changes of the block to the history.
+    pub fn apply_block(&mut self, height: u64, entries: &[(&str, &Value, &Value)]) -> Result<()> {
+        if self.height().map_or(false, |latest| height <= latest) {
+            self.rollback(height)?;
+        }
+        self.blocks.insert(height, self.len);
+        let mut buf = Vec::new();
+        for (prefix, key, value) in entries {
+            let record = Record {
+                height,
+                prefix: prefix.to_string(),
+                key: (*key).clone(),
+                value: (*value).clone(),
+            };
+            let offset = self.len + buf.len() as u64;
+            serde_json::to_writer(&mut buf, &record)?;
+            buf.push(b'\n');
+            self.index_record(&record, offset);
+        }
+        self.file.write_all(&buf)?;
+        self.file.flush()?;
+        self.len += buf.len() as u64;
+        Ok(())
+    }
+
+    /// Get the value of the storage key at the block height, the `height` of the entry
+    /// is the height that the value has been changed at.
+    pub fn get_at(&self, prefix: &str, key: &Value, height: u64) -> Result<Option<StorageEntry>> {
+        let changes = match self.changes(prefix, key) {
+            Some(changes) => changes,
+            None => return Ok(None),
+        };
+        self.read_at(changes, height)
+    }
+
+    /// Get the value of every storage key at the block height, in the order of prefix and key.
+    pub fn snapshot_at(&self, height: u64) -> Result<Vec<(String, StorageEntry)>> {
+        let mut entries = vec![];
+        for (prefix, keys) in &self.index {
+            for changes in keys.values() {
+                if let Some(entry) = self.read_at(changes, height)? {
+                    entries.push((prefix.clone(), entry));
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    fn read_at(&self, changes: &[(u64, u64)], height: u64) -> Result<Option<StorageEntry>> {
+        let pos = changes.partition_point(|(h, _)| *h <= height);
+        if pos == 0 {
+            return Ok(None);
+        }
+        let entry = self.read_entry(changes[pos - 1].1)?;
+        // the storage key has been removed at the height
+        Ok(Some(entry).filter(|entry| !entry.value.is_null()))
+    }
+
+    /// Get the changes of the storage key in the block height range [from, to] (at most `limit`),
+    /// the removal of the key is represented by the `null` value.
+    pub fn history(
+        &self,
+        prefix: &str,
+        key: &Value,
+        from: u64,
+        to: u64,
+        limit: usize,
+    ) -> Result<StorageHistory> {
+        let changes = match self.changes(prefix, key) {
+            Some(changes) => changes,
+            None => return Ok(StorageHistory::default()),
+        };
+        let mut changes = changes
+            .iter()
+            .filter(|(h, _)| from <= *h && *h <= to)
+            .take(limit + 1)
+            .collect::<Vec<_>>();
+        // the key is changed at most once in a block, so the next page starts from the next change
+        let next_height = if changes.len() > limit {
+            changes.pop().map(|(h, _)| *h)
+        } else {
+            None
+        };
+        let entries = changes
+            .into_iter()
+            .map(|(_, offset)| self.read_entry(*offset))
+            .collect::<Result<_>>()?;
+        Ok(StorageHistory {
+            entries,
+            next_height,
+        })
+    }
+
+    /// Get the changes of every storage key after the block height, in the order of block height.
+    pub fn changes_after(&self, height: Option<u64>) -> Result<Vec<(String, StorageEntry)>> {
+        let offset = match height {
+            Some(height) => match self.blocks.range(height + 1..).next() {
+                Some((_, offset)) => *offset,
+                None => return Ok(vec![]),
+            },
+            None => 0,
+        };
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut changes = vec![];
+        let mut line = String::new();
+        let mut pos = offset;
+        while pos < self.len {
+            line.clear();
+            let size = reader.read_line(&mut line)? as u64;
+            if size == 0 {
+                break;
+            }
+            pos += size;
+            let record: Record = serde_json::from_str(&line)?;
+            let entry = StorageEntry {
+                key: record.key,
+                value: record.value,
+                height: record.height,
+            };
+            changes.push((record.prefix, entry));
+        }
+        Ok(changes)
+    }
+
+    fn changes(&self, prefix: &str, key: &Value) -> Option<&Vec<(u64, u64)>> {
+        self.index.get(prefix)?.get(&key.to_string())
+    }
+
+    fn index_record(&mut self, record: &Record, offset: u64) {
+        self.blocks.entry(record.height).or_insert(offset);
+        self.index
+            .entry(record.prefix.clone())
+            .or_default()
+            .entry(record.key.to_string())
+            .or_default()
+            .push((record.height, offset));
+    }
+
+    fn read_entry(&self, offset: u64) -> Result<StorageEntry> {
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        let record: Record = serde_json::from_str(&line)?;
+        Ok(StorageEntry {
+            key: record.key,
+            value: record.value,
+            height: record.height,
+        })
+    }
+
+    /// Remove the records of the block height and the later blocks.
+    pub fn rollback(&mut self, height: u64) -> Result<()> {
+        let offset = match self.blocks.range(height..).next() {
+            Some((_, offset)) => *offset,
+            None => return Ok(()),
+        };
+        info!("Rollback storage history to block #{}", height);
+        self.file.set_len(offset)?;
+        self.len = offset;
+        self.blocks.retain(|h, _| *h < height);
+        for keys in self.index.values_mut() {
+            for changes in keys.values_mut() {
+                while changes.last().map_or(false, |(h, _)| *h >= height) {
+                    changes.pop();
+                }
+            }
+            keys.retain(|_, changes| !changes.is_empty());
+        }
+        self.index.retain(|_, keys| !keys.is_empty());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_history_store() {
+        let path = std::env::temp_dir().join(format!("history-store-{}.jsonl", std::process::id()));
+        let prefix = "XAssets AssetBalance";
+        let key = json!(["0x01", "PCX"]);
+        let mut history = HistoryStore::open(&path).unwrap();
+        history
+            .apply_block(1, &[(prefix, &key, &json!({"Free": 100}))])
+            .unwrap();
+        history.apply_block(2, &[]).unwrap();
+        history
+            .apply_block(3, &[(prefix, &key, &json!({"Free": 300}))])
+            .unwrap();
+        history
+            .apply_block(4, &[(prefix, &key, &Value::Null)])
+            .unwrap();
+
+        assert_eq!(history.get_at(prefix, &key, 0).unwrap(), None);
+        let entry = history.get_at(prefix, &key, 2).unwrap().unwrap();
+        assert_eq!((entry.value, entry.height), (json!({"Free": 100}), 1));
+        assert_eq!(history.get_at(prefix, &key, 3).unwrap().unwrap().height, 3);
+        assert_eq!(history.get_at(prefix, &key, 4).unwrap(), None);
+        let changes = history.history(prefix, &key, 2, 10, 100).unwrap();
+        assert_eq!(changes.entries.len(), 2);
+        assert_eq!(changes.entries[1].value, Value::Null);
+        assert_eq!(changes.next_height, None);
+        // paged by the next height
+        let changes = history.history(prefix, &key, 0, 10, 2).unwrap();
+        assert_eq!(changes.entries.len(), 2);
+        assert_eq!(changes.next_height, Some(4));
+        let changes = history.history(prefix, &key, 4, 10, 2).unwrap();
+        assert_eq!(changes.entries.len(), 1);
+        assert_eq!(changes.next_height, None);
+
+        assert_eq!(history.changes_after(None).unwrap().len(), 3);
+        let changes = history.changes_after(Some(1)).unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].0, prefix);
+        assert_eq!(changes[0].1.height, 3);
+        assert!(history.changes_after(Some(4)).unwrap().is_empty());
+        assert_eq!(history.snapshot_at(3).unwrap().len(), 1);
+        assert_eq!(history.snapshot_at(4).unwrap().len(), 0);
+
+        // sync fallback
+        history
+            .apply_block(3, &[(prefix, &key, &json!({"Free": 200}))])
+            .unwrap();
+        assert_eq!(history.height(), Some(3));
+        let history = HistoryStore::open(&path).unwrap();
+        assert_eq!(history.height(), Some(3));
+        let changes = history.history(prefix, &key, 0, 10, 100).unwrap().entries;
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[1].value, json!({"Free": 200}));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,745 @@
+mod archive;
+mod history;
+mod rpc;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub use self::archive::BlockArchive;
+use self::history::HistoryStore;
+pub use self::rpc::{StoreApi, StoreRpc};
+use crate::Result;
+
+pub type SharedStateStore = Arc<RwLock<StateStore>>;
+pub type SharedBlockArchive = Arc<RwLock<BlockArchive>>;
+
+/// The default limit of entries returned by `listStorage` and `getStorageHistory`.
+pub const DEFAULT_LIST_LIMIT: usize = 100;
+/// The max count of blocks whose undo records are kept (without the storage history),
+/// i.e. the max depth of the sync fallback.
+const MAX_UNDO_BLOCKS: usize = 256;
+/// The max limit of entries returned by `listStorage` and `getStorageHistory`.
+pub const MAX_LIST_LIMIT: usize = 1000;
+
+/// The latest value of the storage key.
+#[derive(PartialEq, Clone, Debug)]
+#[derive(Serialize, Deserialize)]
+pub struct StorageEntry {
+    pub key: Value,
+    pub value: Value,
+    /// The block height that the value has been changed at.
+    pub height: u64,
+}
+
+/// A page of storage entries, the `cursor` is used to get the next page.
+#[derive(PartialEq, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize)]
+pub struct StorageList {
+    pub entries: Vec<StorageEntry>,
+    pub cursor: Option<String>,
+}
+
+/// A page of the changes of storage key, the `next_height` is the `from` of the next page.
+#[derive(PartialEq, Clone, Debug, Default)]
+#[derive(Serialize, Deserialize)]
+pub struct StorageHistory {
+    pub entries: Vec<StorageEntry>,
+    pub next_height: Option<u64>,
+}
+
+/// The header line of the exported snapshot.
+#[derive(Serialize, Deserialize)]
+struct SnapshotHeader {
+    height: u64,
+}
+
+/// The line of the exported snapshot.
+#[derive(Serialize, Deserialize)]
+struct SnapshotEntry {
+    prefix: String,
+    #[serde(flatten)]
+    entry: StorageEntry,
+}
+
+/// The JSON snapshot of state store, which is persisted to the file.
+#[derive(Default, Clone, Serialize, Deserialize)]
+struct Snapshot {
+    height: Option<u64>,
+    /// prefix => (key (json string) => entry)
+    entries: BTreeMap<String, BTreeMap<String, StorageEntry>>,
+}
+
+/// The store of the latest value of every parsed storage key,
+/// which applies the parsed blocks and persists a JSON snapshot to file periodically,
+/// and keeps the versioned history of the storage keys optionally.
+#[derive(Default)]
+pub struct StateStore {
+    path: Option<PathBuf>,
+    snapshot_interval: u64,
+    state: Snapshot,
+    history: Option<HistoryStore>,
+    /// The thread that writes the periodic snapshots, so that the sync isn't blocked by writing.
+    writer: Option<(Sender<Snapshot>, JoinHandle<()>)>,
+    /// block height => the previous entries of the keys changed in the block (prefix, key, entry),
+    /// which undo the block on the sync fallback when the storage history is disabled.
+    undo: BTreeMap<u64, Vec<(String, Value, Option<StorageEntry>)>>,
+}
+
+impl StateStore {
+    /// Create an in-memory state store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Open the state store with the snapshot file, the snapshot will be loaded if it exists,
+    /// and will be saved every `snapshot_interval` blocks.
+    pub fn open<P: AsRef<Path>>(path: P, snapshot_interval: u64) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let state = if path.exists() {
+            let state: Snapshot = serde_json::from_slice(&fs::read(&path)?)?;
+            info!(
+                "Load state snapshot from {:?}, height: {:?}",
+                path, state.height
+            );
+            state
+        } else {
+            Snapshot::default()
+        };
+        Ok(Self {
+            path: Some(path),
+            snapshot_interval,
+            state,
+            history: None,
+            writer: None,
+            undo: BTreeMap::new(),
+        })
+    }
+
+    /// Keep the versioned history of the storage keys in the file, the state is caught up
+    /// with the blocks that are kept in the history after the snapshot.
+    pub fn with_history<P: AsRef<Path>>(mut self, path: P) -> Result<Self> {
+        let history = HistoryStore::open(path)?;
+        let changes = history.changes_after(self.state.height)?;
+        for (prefix, entry) in &changes {
+            self.update(prefix, &entry.key, &entry.value, entry.height);
+        }
+        if history.height() > self.state.height {
+            info!(
+                "Catch up the state from block #{:?} to #{:?} with the storage history, changes: {}",
+                self.state.height,
+                history.height(),
+                changes.len()
+            );
+            self.state.height = history.height();
+        }
+        self.history = Some(history);
+        Ok(self)
+    }
+
+    /// The height of the latest applied block.
+    pub fn height(&self) -> Option<u64> {
+        self.state.height
+    }
+
+    /// Apply the parsed storage entries of the block, the entry with empty value is removed.
+    /// The block that has been applied is applied again on the sync fallback, which rolls back
+    /// the block and the later blocks first.
+    pub fn apply_block(&mut self, height: u64, values: &[Value]) -> Result<()> {
+        self.rollback(height)?;
+        let changes = values
+            .iter()
+            .filter(|value| is_stored(value))
+            .filter_map(|value| Some((value["prefix"].as_str()?, &value["key"], &value["value"])))
+            .collect::<Vec<_>>();
+        if let Some(history) = &mut self.history {
+            history.apply_block(height, &changes)?;
+        } else {
+            let undo = changes
+                .iter()
+                .map(|(prefix, key, _)| {
+                    let entry = self.get(prefix, key).cloned();
+                    (prefix.to_string(), (*key).clone(), entry)
+                })
+                .collect();
+            self.undo.insert(height, undo);
+            while self.undo.len() > MAX_UNDO_BLOCKS {
+                let oldest = *self.undo.keys().next().expect("undo is not empty");
+                self.undo.remove(&oldest);
+            }
+        }
+
+        for (prefix, key, value) in changes {
+            self.update(prefix, key, value, height);
+        }
+        self.state.height = Some(height);
+
+        if self.snapshot_interval != 0 && height % self.snapshot_interval == 0 {
+            self.save_in_background()?;
+        }
+        Ok(())
+    }
+
+    /// Roll back the state to the block before the height, i.e. remove the changes of the block
+    /// and the later blocks, which are restored from the storage history or the undo records.
+    pub fn rollback(&mut self, height: u64) -> Result<()> {
+        if self.state.height.map_or(true, |latest| height > latest) {
+            return Ok(());
+        }
+        info!("Rollback state store to block #{}", height);
+        let previous = height.checked_sub(1);
+        if let Some(history) = &mut self.history {
+            let changed = history.changes_after(previous)?;
+            let mut restored = Vec::with_capacity(changed.len());
+            for (prefix, entry) in changed {
+                let entry = match previous {
+                    Some(previous) => history.get_at(&prefix, &entry.key, previous)?,
+                    None => None,
+                }
+                .ok_or(entry.key);
+                restored.push((prefix, entry));
+            }
+            history.rollback(height)?;
+            for (prefix, entry) in restored {
+                match entry {
+                    Ok(entry) => self.update(&prefix, &entry.key, &entry.value, entry.height),
+                    Err(key) => self.update(&prefix, &key, &Value::Null, height),
+                }
+            }
+        } else {
+            if self
+                .undo
+                .keys()
+                .next()
+                .map_or(true, |oldest| *oldest > height)
+            {
+                warn!(
+                    "The undo records of state store before block #{:?} are absent",
+                    self.undo.keys().next()
+                );
+            }
+            let undo = self.undo.split_off(&height);
+            for (_, records) in undo.into_iter().rev() {
+                for (prefix, key, entry) in records.into_iter().rev() {
+                    match entry {
+                        Some(entry) => self.update(&prefix, &key, &entry.value, entry.height),
+                        None => self.update(&prefix, &key, &Value::Null, height),
+                    }
+                }
+            }
+        }
+        self.state.height = previous;
+        Ok(())
+    }
+
+    /// Update the latest value of the storage key, the key is removed if the value is `null`.
+    fn update(&mut self, prefix: &str, key: &Value, value: &Value, height: u64) {
+        if value.is_null() {
+            if let Some(entries) = self.state.entries.get_mut(prefix) {
+                entries.remove(&key.to_string());
+            }
+        } else {
+            let entry = StorageEntry {
+                key: key.clone(),
+                value: value.clone(),
+                height,
+            };
+            self.state
+                .entries
+                .entry(prefix.to_string())
+                .or_default()
+                .insert(key.to_string(), entry);
+        }
+    }
+
+    /// Get the latest value of the storage key (`null` for the storage value).
+    pub fn get(&self, prefix: &str, key: &Value) -> Option<&StorageEntry> {
+        self.state.entries.get(prefix)?.get(&key.to_string())
+    }
+
+    /// The latest values of all storage keys of the prefix, in the order of keys.
+    pub fn entries<'a>(&'a self, prefix: &str) -> impl Iterator<Item = &'a StorageEntry> {
+        self.state
+            .entries
+            .get(prefix)
+            .into_iter()
+            .flat_map(BTreeMap::values)
+    }
+
+    /// Get the value of the storage key at the block height.
+    pub fn get_at(&self, prefix: &str, key: &Value, height: u64) -> Result<Option<StorageEntry>> {
+        self.history_store()?.get_at(prefix, key, height)
+    }
+
+    /// Get the changes of the storage key in the block height range [from, to] (at most `limit`).
+    pub fn history_of(
+        &self,
+        prefix: &str,
+        key: &Value,
+        from: u64,
+        to: u64,
+        limit: usize,
+    ) -> Result<StorageHistory> {
+        self.history_store()?.history(prefix, key, from, to, limit)
+    }
+
+    fn history_store(&self) -> Result<&HistoryStore> {
+        self.history
+            .as_ref()
+            .ok_or_else(|| "Storage history is disabled".into())
+    }
+
+    /// Check whether the snapshot of state at the block height is available,
+    /// which requires the storage history unless it's the latest height.
+    pub fn check_snapshot(&self, height: u64) -> Result<()> {
+        match self.state.height {
+            Some(latest) if height == latest => Ok(()),
+            Some(latest) if height < latest => self.history_store().map(|_| ()),
+            _ => Err(format!(
+                "Snapshot at block #{} is unavailable, the latest block: {:?}",
+                height, self.state.height
+            )
+            .into()),
+        }
+    }
+
+    /// Get the value of every storage key at the block height, in the order of prefix and key.
+    pub fn snapshot_at(&self, height: u64) -> Result<Vec<(String, StorageEntry)>> {
+        self.check_snapshot(height)?;
+        if self.state.height == Some(height) {
+            Ok(self
+                .state
+                .entries
+                .iter()
+                .flat_map(|(prefix, entries)| {
+                    entries
+                        .values()
+                        .map(move |entry| (prefix.clone(), entry.clone()))
+                })
+                .collect())
+        } else {
+            self.history_store()?.snapshot_at(height)
+        }
+    }
+
+    /// Export the snapshot of state at the block height as JSON lines,
+    /// the first line is `{"height":<height>}`, and the others are `{"prefix","key","value","height"}`.
+    pub fn export_snapshot<W: Write>(&self, height: u64, mut out: W) -> Result<usize> {
+        let entries = self.snapshot_at(height)?;
+        serde_json::to_writer(&mut out, &SnapshotHeader { height })?;
+        out.write_all(b"\n")?;
+        for (prefix, entry) in &entries {
+            let line = SnapshotEntry {
+                prefix: prefix.clone(),
+                entry: entry.clone(),
+            };
+            serde_json::to_writer(&mut out, &line)?;
+            out.write_all(b"\n")?;
+        }
+        out.flush()?;
+        info!(
+            "Export snapshot at block #{}, entries: {}",
+            height,
+            entries.len()
+        );
+        Ok(entries.len())
+    }
+
+    /// Import the exported snapshot, which replaces the whole state,
+    /// and returns the block height of the snapshot.
+    pub fn import_snapshot<R: BufRead>(&mut self, input: R) -> Result<u64> {
+        let mut lines = input.lines();
+        let header: SnapshotHeader = match lines.next() {
+            Some(line) => serde_json::from_str(&line?)?,
+            None => return Err("Empty snapshot".into()),
+        };
+        let mut state = Snapshot {
+            height: Some(header.height),
+            entries: BTreeMap::new(),
+        };
+        for line in lines {
+            let SnapshotEntry { prefix, entry } = serde_json::from_str(&line?)?;
+            state
+                .entries
+                .entry(prefix)
+                .or_default()
+                .insert(entry.key.to_string(), entry);
+        }
+        let count = state.entries.values().map(BTreeMap::len).sum::<usize>();
+        if let Some(history) = &mut self.history {
+            let changes = state
+                .entries
+                .iter()
+                .flat_map(|(prefix, entries)| {
+                    entries
+                        .values()
+                        .map(move |entry| (prefix.as_str(), &entry.key, &entry.value))
+                })
+                .collect::<Vec<_>>();
+            history.apply_block(header.height, &changes)?;
+        }
+        self.state = state;
+        self.save()?;
+        info!(
+            "Import snapshot at block #{}, entries: {}",
+            header.height, count
+        );
+        Ok(header.height)
+    }
+
+    /// List the storage entries of the prefix in the order of keys,
+    /// whose keys start with the `key_prefix`, and after the `cursor` (exclusive).
+    pub fn list(
+        &self,
+        prefix: &str,
+        key_prefix: Option<&str>,
+        limit: usize,
+        cursor: Option<&str>,
+    ) -> StorageList {
+        use std::ops::Bound::{Excluded, Unbounded};
+
+        let entries = match self.state.entries.get(prefix) {
+            Some(entries) => entries,
+            None => return StorageList::default(),
+        };
+        let start = match cursor {
+            Some(cursor) => Excluded(cursor.to_string()),
+            None => Unbounded,
+        };
+        let mut list = StorageList::default();
+        for (key, entry) in entries
+            .range((start, Unbounded))
+            .filter(|(_, entry)| key_prefix.map_or(true, |p| key_text(&entry.key).starts_with(p)))
+        {
+            if list.entries.len() == limit {
+                break;
+            }
+            list.entries.push(entry.clone());
+            list.cursor = Some(key.clone());
+        }
+        if list.entries.len() < limit {
+            list.cursor = None;
+        }
+        list
+    }
+
+    /// Save the snapshot to the file.
+    pub fn save(&self) -> Result<()> {
+        match &self.path {
+            Some(path) => write_snapshot(path, &self.state),
+            None => Ok(()),
+        }
+    }
+
+    /// Save the snapshot to the file in the background, the state is cloned (under the lock
+    /// of store) and then written by the writer thread.
+    fn save_in_background(&mut self) -> Result<()> {
+        let path = match &self.path {
+            Some(path) => path.clone(),
+            None => return Ok(()),
+        };
+        let (tx, _) = self.writer.get_or_insert_with(|| {
+            let (tx, rx) = mpsc::channel::<Snapshot>();
+            let handle = thread::spawn(move || {
+                while let Ok(mut state) = rx.recv() {
+                    // only the latest snapshot is written if the writing falls behind
+                    while let Ok(latest) = rx.try_recv() {
+                        state = latest;
+                    }
+                    if let Err(err) = write_snapshot(&path, &state) {
+                        error!("Unable to save state snapshot to {:?}: {}", path, err);
+                    }
+                }
+            });
+            (tx, handle)
+        });
+        tx.send(self.state.clone())
+            .map_err(|_| "The state snapshot writer has exited".into())
+    }
+}
+
+impl Drop for StateStore {
+    /// Wait for the pending snapshot to be written.
+    fn drop(&mut self) {
+        if let Some((tx, handle)) = self.writer.take() {
+            drop(tx);
+            if handle.join().is_err() {
+                error!("The state snapshot writer panicked");
+            }
+        }
+    }
+}
+
+/// Write the snapshot to the file (write to a temporary file and then rename it).
+fn write_snapshot(path: &Path, state: &Snapshot) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, serde_json::to_vec(state)?)?;
+    fs::rename(&tmp, path)?;
+    debug!(
+        "Save state snapshot to {:?}, height: {:?}",
+        path, state.height
+    );
+    Ok(())
+}
+
+/// The entries that can't be parsed and the events are not kept.
+fn is_stored(value: &Value) -> bool {
+    !matches!(
+        value["type"].as_str(),
+        Some("unknown") | Some("error") | Some("event")
+    )
+}
+
+/// The text of key that is used to match the key prefix.
+fn key_text(key: &Value) -> String {
+    match key.as_str() {
+        Some(key) => key.to_string(),
+        None => key.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn balance(account: &str, token: &str, free: u64) -> Value {
+        json!({
+            "type":"map",
+            "prefix":"XAssets AssetBalance",
+            "key":[account, token],
+            "value": if free == 0 { Value::Null } else { json!({"Free": free}) },
+        })
+    }
+
+    #[test]
+    fn test_state_store_apply_block() {
+        let mut store = StateStore::new();
+        store
+            .apply_block(
+                1,
+                &[
+                    balance("0x01", "PCX", 100),
+                    balance("0x01", "BTC", 200),
+                    json!({"type":"value", "prefix":"Timestamp Now", "key":null, "value":1000}),
+                    json!({"type":"error", "prefix":"XAssets AssetBalance", "key":"0x00", "value":"0x00"}),
+                ],
+            )
+            .unwrap();
+        assert_eq!(store.height(), Some(1));
+        assert_eq!(
+            store.get("Timestamp Now", &Value::Null),
+            Some(&StorageEntry {
+                key: Value::Null,
+                value: json!(1000),
+                height: 1
+            })
+        );
+
+        store
+            .apply_block(2, &[balance("0x01", "PCX", 0), balance("0x02", "PCX", 300)])
+            .unwrap();
+        assert_eq!(
+            store.get("XAssets AssetBalance", &json!(["0x01", "PCX"])),
+            None
+        );
+        let entry = store
+            .get("XAssets AssetBalance", &json!(["0x02", "PCX"]))
+            .unwrap();
+        assert_eq!(entry.value, json!({"Free": 300}));
+        assert_eq!(entry.height, 2);
+        assert_eq!(store.get("XAssets AssetBalance", &json!("0x00")), None);
+    }
+
+    #[test]
+    fn test_state_store_list() {
+        let mut store = StateStore::new();
+        store
+            .apply_block(
+                1,
+                &[
+                    balance("0x01", "BTC", 100),
+                    balance("0x01", "PCX", 200),
+                    balance("0x02", "PCX", 300),
+                ],
+            )
+            .unwrap();
+
+        let list = store.list("XAssets AssetBalance", None, 2, None);
+        assert_eq!(list.entries.len(), 2);
+        assert_eq!(list.entries[0].key, json!(["0x01", "BTC"]));
+        let cursor = list.cursor.unwrap();
+        let list = store.list("XAssets AssetBalance", None, 2, Some(&cursor));
+        assert_eq!(list.entries.len(), 1);
+        assert_eq!(list.entries[0].key, json!(["0x02", "PCX"]));
+        assert_eq!(list.cursor, None);
+
+        let list = store.list("XAssets AssetBalance", Some("[\"0x01\""), 10, None);
+        assert_eq!(list.entries.len(), 2);
+        assert_eq!(
+            store.list("XAssets AssetInfo", None, 10, None),
+            StorageList::default()
+        );
+    }
+
+    #[test]
+    fn test_state_store_snapshot() {
+        let mut store = StateStore::new();
+        store
+            .apply_block(1, &[balance("0x01", "PCX", 100)])
+            .unwrap();
+        store
+            .apply_block(2, &[balance("0x02", "PCX", 200)])
+            .unwrap();
+        assert!(store.check_snapshot(1).is_err());
+        assert!(store.check_snapshot(3).is_err());
+
+        let mut out = vec![];
+        assert_eq!(store.export_snapshot(2, &mut out).unwrap(), 2);
+        let mut imported = StateStore::new();
+        assert_eq!(imported.import_snapshot(out.as_slice()).unwrap(), 2);
+        assert_eq!(imported.height(), Some(2));
+        let entry = imported
+            .get("XAssets AssetBalance", &json!(["0x01", "PCX"]))
+            .unwrap();
+        assert_eq!(entry.value, json!({"Free": 100}));
+        assert_eq!(entry.height, 1);
+        assert_eq!(
+            imported.snapshot_at(2).unwrap(),
+            store.snapshot_at(2).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_state_store_fallback() {
+        let dir = std::env::temp_dir().join(format!("state-store-fallback-{}", std::process::id()));
+        let with_history = StateStore::new()
+            .with_history(dir.join("history.jsonl"))
+            .unwrap();
+        for mut store in vec![StateStore::new(), with_history] {
+            store
+                .apply_block(1, &[balance("0x01", "PCX", 100)])
+                .unwrap();
+            store
+                .apply_block(
+                    2,
+                    &[balance("0x01", "PCX", 200), balance("0x02", "PCX", 300)],
+                )
+                .unwrap();
+            store.apply_block(3, &[balance("0x01", "PCX", 0)]).unwrap();
+
+            // the blocks #2 and #3 are abandoned
+            store
+                .apply_block(2, &[balance("0x03", "PCX", 400)])
+                .unwrap();
+            assert_eq!(store.height(), Some(2));
+            let entry = store
+                .get("XAssets AssetBalance", &json!(["0x01", "PCX"]))
+                .unwrap();
+            assert_eq!(
+                (entry.value.clone(), entry.height),
+                (json!({"Free": 100}), 1)
+            );
+            assert_eq!(
+                store.get("XAssets AssetBalance", &json!(["0x02", "PCX"])),
+                None
+            );
+            assert!(store
+                .get("XAssets AssetBalance", &json!(["0x03", "PCX"]))
+                .is_some());
+            assert_eq!(store.entries("XAssets AssetBalance").count(), 2);
+            if store.history.is_some() {
+                assert_eq!(
+                    store
+                        .get_at("XAssets AssetBalance", &json!(["0x01", "PCX"]), 2)
+                        .unwrap()
+                        .as_ref(),
+                    Some(entry)
+                );
+            }
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_state_store_catch_up() {
+        let dir = std::env::temp_dir().join(format!("state-store-catch-up-{}", std::process::id()));
+        let (path, history_path) = (dir.join("state.json"), dir.join("history.jsonl"));
+        let mut store = StateStore::open(&path, 2)
+            .unwrap()
+            .with_history(&history_path)
+            .unwrap();
+        store
+            .apply_block(1, &[balance("0x01", "PCX", 100)])
+            .unwrap();
+        store
+            .apply_block(2, &[balance("0x02", "PCX", 200)])
+            .unwrap();
+        store
+            .apply_block(3, &[balance("0x01", "PCX", 0), balance("0x03", "PCX", 300)])
+            .unwrap();
+        drop(store);
+
+        // the snapshot is saved at block 2, and the block 3 is kept in the history
+        let reopen = || {
+            StateStore::open(&path, 2)
+                .unwrap()
+                .with_history(&history_path)
+                .unwrap()
+        };
+        let store = reopen();
+        assert_eq!(store.height(), Some(3));
+        assert_eq!(
+            store.get("XAssets AssetBalance", &json!(["0x01", "PCX"])),
+            None
+        );
+        let entry = store
+            .get("XAssets AssetBalance", &json!(["0x03", "PCX"]))
+            .unwrap();
+        assert_eq!(
+            (entry.value.clone(), entry.height),
+            (json!({"Free": 300}), 3)
+        );
+        let history = store
+            .history_of("XAssets AssetBalance", &json!(["0x01", "PCX"]), 0, 10, 10)
+            .unwrap();
+        assert_eq!(history.entries.len(), 2);
+        drop(store);
+        assert_eq!(reopen().height(), Some(3));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_state_store_persistence() {
+        let path = std::env::temp_dir().join(format!("state-store-{}.json", std::process::id()));
+        let mut store = StateStore::open(&path, 2).unwrap();
+        store
+            .apply_block(1, &[balance("0x01", "PCX", 100)])
+            .unwrap();
+        assert!(!path.exists());
+        store
+            .apply_block(2, &[balance("0x02", "PCX", 200)])
+            .unwrap();
+        // the snapshot is written by the writer thread, which is joined when the store is dropped
+        drop(store);
+        assert!(path.exists());
+
+        let store = StateStore::open(&path, 2).unwrap();
+        assert_eq!(store.height(), Some(2));
+        assert!(store
+            .get("XAssets AssetBalance", &json!(["0x01", "PCX"]))
+            .is_some());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,109 @@
+use jsonrpc_derive::rpc;
+use serde_json::Value;
+
+use super::{
+    SharedStateStore, StorageEntry, StorageHistory, StorageList, DEFAULT_LIST_LIMIT, MAX_LIST_LIMIT,
+};
+use crate::Result;
+
+/// Storage query API
+#[rpc(server)]
+pub trait StoreApi {
+    /// Get the latest value of the storage key (omit the key for the storage value).
+    #[rpc(name = "getStorage")]
+    fn get_storage(&self, prefix: String, key: Option<Value>) -> Result<Option<StorageEntry>>;
+
+    /// List the latest values of the storage keys of the prefix.
+    #[rpc(name = "listStorage")]
+    fn list_storage(
+        &self,
+        prefix: String,
+        key_prefix: Option<String>,
+        limit: Option<usize>,
+        cursor: Option<String>,
+    ) -> Result<StorageList>;
+
+    /// Get the value of the storage key at the block height (`null` key for the storage value).
+    #[rpc(name = "getStorageAt")]
+    fn get_storage_at(
+        &self,
+        prefix: String,
+        key: Value,
+        height: u64,
+    ) -> Result<Option<StorageEntry>>;
+
+    /// Get the changes of the storage key in the block height range [from, to].
+    #[rpc(name = "getStorageHistory")]
+    fn get_storage_history(
+        &self,
+        prefix: String,
+        key: Value,
+        from: Option<u64>,
+        to: Option<u64>,
+        limit: Option<usize>,
+    ) -> Result<StorageHistory>;
+}
+
+pub struct StoreRpc {
+    store: SharedStateStore,
+}
+
+impl StoreRpc {
+    pub fn new(store: SharedStateStore) -> Self {
+        Self { store }
+    }
+}
+
+impl StoreApi for StoreRpc {
+    fn get_storage(&self, prefix: String, key: Option<Value>) -> Result<Option<StorageEntry>> {
+        let key = key.unwrap_or_default();
+        Ok(self.store.read().get(&prefix, &key).cloned())
+    }
+
+    fn list_storage(
+        &self,
+        prefix: String,
+        key_prefix: Option<String>,
+        limit: Option<usize>,
+        cursor: Option<String>,
+    ) -> Result<StorageList> {
+        let limit = check_limit(limit)?;
+        Ok(self
+            .store
+            .read()
+            .list(&prefix, key_prefix.as_deref(), limit, cursor.as_deref()))
+    }
+
+    fn get_storage_at(
+        &self,
+        prefix: String,
+        key: Value,
+        height: u64,
+    ) -> Result<Option<StorageEntry>> {
+        self.store.read().get_at(&prefix, &key, height)
+    }
+
+    fn get_storage_history(
+        &self,
+        prefix: String,
+        key: Value,
+        from: Option<u64>,
+        to: Option<u64>,
+        limit: Option<usize>,
+    ) -> Result<StorageHistory> {
+        let (from, to) = (from.unwrap_or(0), to.unwrap_or(u64::max_value()));
+        if from > to {
+            return Err("Invalid block height range".into());
+        }
+        let limit = check_limit(limit)?;
+        self.store.read().history_of(&prefix, &key, from, to, limit)
+    }
+}
+
+fn check_limit(limit: Option<usize>) -> Result<usize> {
+    let limit = limit.unwrap_or(DEFAULT_LIST_LIMIT);
+    if limit == 0 || limit > MAX_LIST_LIMIT {
+        return Err(format!("Invalid limit, range: [1, {}]", MAX_LIST_LIMIT).into());
+    }
+    Ok(limit)
+}
@@ -34,9 +34,10 @@ impl Tail {
         Tail::default()
     }
 
-    pub fn run(&self, config: &CliConfig) -> Result<thread::JoinHandle<()>> {
+    /// Scan the sync log from the start height (which may be after the configured one).
+    pub fn run(&self, config: &CliConfig, start_height: u64) -> Result<thread::JoinHandle<()>> {
         let tx = self.tx.clone();
-        let mut tail_impl = TailImpl::new(tx, config)?;
+        let mut tail_impl = TailImpl::new(tx, config, start_height)?;
         let handle = thread::spawn(move || {
             tail_impl.run();
             error!("Tail thread exists abnormally");
@@ -63,7 +64,11 @@ pub struct TailImpl {
 }
 
 impl TailImpl {
-    pub fn new(tx: mpsc::Sender<StorageData>, config: &CliConfig) -> Result<Self> {
+    pub fn new(
+        tx: mpsc::Sender<StorageData>,
+        config: &CliConfig,
+        start_height: u64,
+    ) -> Result<Self> {
         info!("Start reading sync log [path: {:?}]", &config.sync_log_path);
         let sync_log_file = read_sync_log_file(&config.sync_log_path)?;
         let reader = BufReader::with_capacity(10 * BUFFER_SIZE, sync_log_file);
@@ -71,7 +76,7 @@ impl TailImpl {
         Ok(Self {
             tx,
             sync_log_path: config.sync_log_path.clone(),
-            start_height: config.start_height,
+            start_height,
             stop_height: config.stop_height,
             reader,
             line,
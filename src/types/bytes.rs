@@ -3,6 +3,9 @@ use serde::{
     de::{Error, Visitor},
     Deserialize, Deserializer, Serialize, Serializer,
 };
+use serde_json::Value;
+
+use super::schema::{self, Schema};
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Default, Debug)]
 #[derive(Encode, Decode)]
@@ -46,6 +49,12 @@ impl Serialize for Bytes {
     }
 }
 
+impl Schema for Bytes {
+    fn schema() -> Value {
+        schema::hex(None)
+    }
+}
+
 impl<'de> Deserialize<'de> for Bytes {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
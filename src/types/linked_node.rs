@@ -4,6 +4,9 @@ use std::fmt::Debug;
 
 use parity_codec::{Codec, Decode, Encode};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+
+use super::schema::{field, object, Schema};
 
 /// A type that implements Serialize, DeserializeOwned and Debug when in std environment.
 pub trait MaybeSerializeDebug: Serialize + DeserializeOwned + Debug {}
@@ -23,6 +26,19 @@ pub struct Node<T: NodeT> {
     pub data: T,
 }
 
+impl<T: NodeT + Schema> Schema for Node<T>
+where
+    T::Index: Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            ("prev", field(|v: &Self| &v.prev)),
+            ("next", field(|v: &Self| &v.next)),
+            ("data", field(|v: &Self| &v.data)),
+        ])
+    }
+}
+
 #[derive(PartialEq, Eq, Clone, Default, Debug)]
 #[derive(Encode, Decode)]
 #[derive(Serialize, Deserialize)]
@@ -41,3 +57,35 @@ where
     multi_key: K,
     index: T::Index,
 }
+
+impl<K, T> Schema for MultiNodeIndex<K, T>
+where
+    K: Codec + Clone + Eq + PartialEq + Default + Schema,
+    T: NodeT,
+    T::Index: Schema,
+{
+    fn schema() -> Value {
+        object(vec![
+            ("multi_key", field(|v: &Self| &v.multi_key)),
+            ("index", field(|v: &Self| &v.index)),
+        ])
+    }
+}
+
+/// The linkage of the node of substrate `linked_map`, which is stored with the value.
+#[derive(PartialEq, Eq, Clone, Default, Debug)]
+#[derive(Encode, Decode)]
+#[derive(Serialize, Deserialize)]
+pub struct Linkage<Key> {
+    pub previous: Option<Key>,
+    pub next: Option<Key>,
+}
+
+impl<Key: Schema> Schema for Linkage<Key> {
+    fn schema() -> Value {
+        object(vec![
+            ("previous", field(|v: &Self| &v.previous)),
+            ("next", field(|v: &Self| &v.next)),
+        ])
+    }
+}
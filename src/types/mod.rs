@@ -1,6 +1,8 @@
 pub mod btc;
 pub mod bytes;
 pub mod linked_node;
+pub mod schema;
 
 pub use self::bytes::Bytes;
-pub use self::linked_node::{MultiNodeIndex, Node, NodeIndex, NodeT};
+pub use self::linked_node::{Linkage, MultiNodeIndex, Node, NodeIndex, NodeT};
+pub use self::schema::Schema;
@@ -0,0 +1,147 @@
+//! The JSON Schemas of the decoded types, which are implemented explicitly next to the types
+//! (together with their `Serialize`), and are checked against the serialized default values by tests.
+
+use std::collections::BTreeMap;
+
+use primitive_types::{H160, H256, H512};
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// The type that describes its JSON Schema as it's serialized.
+pub trait Schema {
+    fn schema() -> Value;
+}
+
+pub fn uint() -> Value {
+    json!({"type": "integer", "minimum": 0})
+}
+
+/// The `0x` prefixed hex string, of the given length of bytes (if any).
+pub fn hex(len: Option<usize>) -> Value {
+    match len {
+        Some(len) => json!({"type": "string", "pattern": format!("^0x[0-9a-f]{{{}}}$", len * 2)}),
+        None => json!({"type": "string", "pattern": "^0x([0-9a-f]{2})*$"}),
+    }
+}
+
+/// The schema that also allows `null`.
+pub fn nullable(schema: Value) -> Value {
+    if schema["type"] == "null" {
+        return schema;
+    }
+    json!({"anyOf": [schema, {"type": "null"}]})
+}
+
+/// The object with all the properties required, e.g. the struct with named fields.
+pub fn object(properties: Vec<(&str, Value)>) -> Value {
+    let required = properties.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+    let properties = properties
+        .into_iter()
+        .map(|(name, schema)| (name.to_string(), schema))
+        .collect::<serde_json::Map<_, _>>();
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+/// The array of fixed length, e.g. the tuple or the tuple struct.
+pub fn tuple(items: Vec<Value>) -> Value {
+    json!({
+        "type": "array",
+        "prefixItems": items,
+        "minItems": items.len(),
+        "maxItems": items.len(),
+    })
+}
+
+/// The schema of the field, whose type is inferred from the accessor, e.g. `field(|v: &Self| &v.id)`.
+pub fn field<S, T: Schema, F: Fn(&S) -> &T>(_accessor: F) -> Value {
+    T::schema()
+}
+
+/// The enum of unit variants, which are serialized as the names of variants.
+pub fn variants<T: Serialize>(variants: &[T]) -> Value {
+    let names = variants
+        .iter()
+        .map(|variant| serde_json::to_value(variant).unwrap_or_default())
+        .collect::<Vec<_>>();
+    json!({"type": "string", "enum": names})
+}
+
+macro_rules! impl_schema {
+    ($($ty:ty),* => $schema:expr) => {
+        $(
+            impl Schema for $ty {
+                fn schema() -> Value {
+                    $schema
+                }
+            }
+        )*
+    };
+}
+
+impl_schema!(u8, u16, u32, u64, u128 => uint());
+impl_schema!(bool => json!({"type": "boolean"}));
+impl_schema!(String => json!({"type": "string"}));
+impl_schema!(() => json!({"type": "null"}));
+impl_schema!(H160 => hex(Some(20)));
+impl_schema!(H256 => hex(Some(32)));
+impl_schema!(H512 => hex(Some(64)));
+
+impl<T: Schema> Schema for Vec<T> {
+    fn schema() -> Value {
+        json!({"type": "array", "items": T::schema()})
+    }
+}
+
+impl<T: Schema> Schema for Option<T> {
+    fn schema() -> Value {
+        nullable(T::schema())
+    }
+}
+
+impl<K: Schema, V: Schema> Schema for BTreeMap<K, V> {
+    fn schema() -> Value {
+        let mut schema = json!({"type": "object", "additionalProperties": V::schema()});
+        let key = K::schema();
+        if key["type"] == "string" {
+            schema["propertyNames"] = key;
+        }
+        schema
+    }
+}
+
+macro_rules! impl_tuple_schema {
+    ($($name:ident),+) => {
+        impl<$($name: Schema),+> Schema for ($($name,)+) {
+            fn schema() -> Value {
+                tuple(vec![$($name::schema()),+])
+            }
+        }
+    };
+}
+
+impl_tuple_schema!(A, B);
+impl_tuple_schema!(A, B, C);
+impl_tuple_schema!(A, B, C, D);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema() {
+        assert_eq!(<Option<u32>>::schema()["anyOf"][0], uint());
+        assert_eq!(<Option<()>>::schema(), json!({"type": "null"}));
+        assert_eq!(
+            <(u32, String)>::schema()["prefixItems"][1]["type"],
+            "string"
+        );
+        let map = <BTreeMap<String, bool>>::schema();
+        assert_eq!(map["propertyNames"]["type"], "string");
+        assert_eq!(map["additionalProperties"]["type"], "boolean");
+    }
+}